@@ -1,12 +1,19 @@
-use std::char;
+use std::ops::Index;
 
-#[derive(Clone, Copy, PartialEq)]
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Cell {
     Floor,
     Wall,
     Piece,
     Goal,
-    Zone(u32),
+    PieceOnGoal,
+    /// Player for push rules (see the push module).
+    Player,
+    PlayerOnGoal,
 }
 
 fn cell_symbol(cell: &Cell) -> char {
@@ -15,49 +22,271 @@ fn cell_symbol(cell: &Cell) -> char {
         Cell::Wall => 'X',
         Cell::Piece => '#',
         Cell::Goal => '@',
-        Cell::Zone(num) => char::from_digit(*num, 10).unwrap(),
+        Cell::PieceOnGoal => '*',
+        Cell::Player => 'P',
+        Cell::PlayerOnGoal => '+',
     }
 }
 
 pub fn is_floor(cell: Cell) -> bool {
-    return match cell {
-        Cell::Floor => true,
-        _ => false,
-    };
+    return matches!(cell, Cell::Floor);
 }
 
 pub fn is_wall(cell: Cell) -> bool {
-    return match cell {
-        Cell::Wall => true,
-        _ => false,
-    };
+    return matches!(cell, Cell::Wall);
 }
 
 pub fn is_goal(cell: Cell) -> bool {
-    return match cell {
-        Cell::Goal => true,
-        _ => false,
-    };
+    return matches!(cell, Cell::Goal);
+}
+
+/// True for cells holding a piece, whether or not it stands on a goal.
+pub fn has_piece(cell: Cell) -> bool {
+    return matches!(cell, Cell::Piece | Cell::PieceOnGoal);
 }
 
-pub type Board = Vec<Vec<Cell>>;
+/// True for cells holding a goal, whether or not something stands on it.
+pub fn has_goal(cell: Cell) -> bool {
+    return matches!(cell, Cell::Goal | Cell::PieceOnGoal | Cell::PlayerOnGoal);
+}
+
+/// True for cells holding the player, whether or not it stands on a goal.
+pub fn has_player(cell: Cell) -> bool {
+    return matches!(cell, Cell::Player | Cell::PlayerOnGoal);
+}
+
+/// Maximum number of cells (width * height) a Board can hold.
+pub const MAX_CELLS: usize = 256;
+
+/// Fixed-size set of board cells, stored as a bitmask. Cell (x, y) has index y * width + x.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CellSet([u64; MAX_CELLS / 64]);
+
+impl CellSet {
+    pub fn contains(&self, idx: usize) -> bool {
+        return self.0[idx / 64] >> (idx % 64) & 1 == 1;
+    }
+
+    pub fn insert(&mut self, idx: usize) {
+        self.0[idx / 64] |= 1 << (idx % 64);
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        self.0[idx / 64] &= !(1 << (idx % 64));
+    }
+
+    fn set(&mut self, idx: usize, value: bool) {
+        if value {
+            self.insert(idx);
+        } else {
+            self.remove(idx);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.0.iter().map(|word| word.count_ones() as usize).sum();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.0.iter().all(|word| *word == 0);
+    }
+
+    /// True if every index in other is also in self.
+    pub fn is_superset(&self, other: &CellSet) -> bool {
+        return self.0.iter().zip(other.0.iter()).all(|(mine, theirs)| theirs & !mine == 0);
+    }
+
+    /// Iterate over the indices in the set, in increasing order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        (0..MAX_CELLS).filter(move |idx| self.contains(*idx))
+    }
+}
+
+/// Grid of cells, indexed as board[(x, y)].
+///
+/// Walls and floor, pieces and goals are each stored as a CellSet; the player, if any, as coordinates.
+/// Boards are cheap to clone, can be hashed and have a total order (used to pick canonical forms).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::json::BoardJson", try_from = "crate::json::BoardJson"))]
+pub struct Board {
+    width: u8,
+    height: u8,
+    /// Non-wall cells.
+    floor: CellSet,
+    pieces: CellSet,
+    goals: CellSet,
+    player: Option<(u8, u8)>,
+}
+
+impl Board {
+    /// Create a board of the given dimensions, made only of walls.
+    ///
+    /// Panics if the board has more than MAX_CELLS cells.
+    pub fn new(width: u8, height: u8) -> Board {
+        assert!(width as usize * height as usize <= MAX_CELLS, "board too large: {}x{}", width, height);
+        return Board {
+            width: width,
+            height: height,
+            floor: CellSet::default(),
+            pieces: CellSet::default(),
+            goals: CellSet::default(),
+            player: None,
+        };
+    }
+
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.height as usize
+    }
+
+    /// Returns the (width, height) of the board.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    /// Index of cell (x, y) in the board's CellSets. Panics if out of bounds.
+    pub fn cell_index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width() && y < self.height(), "({}, {}) out of bounds", x, y);
+        return y * self.width() + x;
+    }
+
+    /// Position of the cell with the given CellSet index.
+    pub fn position(&self, idx: usize) -> (u8, u8) {
+        ((idx % self.width()) as u8, (idx / self.width()) as u8)
+    }
+
+    /// Positions of all pieces, in row-major order.
+    pub fn pieces(&self) -> Vec<(u8, u8)> {
+        self.pieces.iter().map(|idx| self.position(idx)).collect()
+    }
+
+    /// Positions of all goals, in row-major order.
+    pub fn goals(&self) -> Vec<(u8, u8)> {
+        self.goals.iter().map(|idx| self.position(idx)).collect()
+    }
+
+    /// Cells holding a piece.
+    pub fn piece_set(&self) -> CellSet {
+        self.pieces
+    }
+
+    /// Cells holding a goal.
+    pub fn goal_set(&self) -> CellSet {
+        self.goals
+    }
+
+    /// Replace all pieces. Panics if any of them would stand on a wall.
+    pub fn set_pieces(&mut self, pieces: CellSet) {
+        assert!(self.floor.is_superset(&pieces), "pieces must stand on floor");
+        self.pieces = pieces;
+    }
+
+    /// Position of the player, if any.
+    pub fn player(&self) -> Option<(u8, u8)> {
+        self.player
+    }
+
+    /// Move the player, or remove it with None. Panics if the position is a wall.
+    pub fn set_player(&mut self, player: Option<(u8, u8)>) {
+        if let Some((x, y)) = player {
+            assert!(self.floor.contains(self.cell_index(x as usize, y as usize)), "player must stand on floor");
+        }
+        self.player = player;
+    }
+
+    /// Number of non-wall cells.
+    pub fn floor_count(&self) -> usize {
+        self.floor.len()
+    }
+
+    /// True if there are goals and all of them are covered by pieces.
+    pub fn is_solved(&self) -> bool {
+        !self.goals.is_empty() && self.pieces.is_superset(&self.goals)
+    }
+
+    /// Returns the cell at (x, y). Panics if out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        let idx = self.cell_index(x, y);
+        if !self.floor.contains(idx) {
+            return Cell::Wall;
+        }
+
+        let is_player = self.player == Some((x as u8, y as u8));
+        return match (is_player, self.pieces.contains(idx), self.goals.contains(idx)) {
+            (true, _, true) => Cell::PlayerOnGoal,
+            (true, _, false) => Cell::Player,
+            (false, true, true) => Cell::PieceOnGoal,
+            (false, true, false) => Cell::Piece,
+            (false, false, true) => Cell::Goal,
+            (false, false, false) => Cell::Floor,
+        };
+    }
+
+    /// Overwrite the cell at (x, y). Panics if out of bounds.
+    ///
+    /// There is at most one player: placing it moves it from its previous position.
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        let idx = self.cell_index(x, y);
+        let pos = Some((x as u8, y as u8));
+        self.floor.set(idx, cell != Cell::Wall);
+        self.pieces.set(idx, has_piece(cell));
+        self.goals.set(idx, has_goal(cell));
+        if has_player(cell) {
+            self.player = pos;
+        } else if self.player == pos {
+            self.player = None;
+        }
+    }
+
+    /// Iterate over all cells in row-major order, along with their (x, y) positions.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), Cell)> + '_ {
+        let width = self.width();
+        (0..width * self.height()).map(move |idx| {
+            let pos = (idx % width, idx / width);
+            (pos, self.get(pos.0, pos.1))
+        })
+    }
+
+    /// Iterate over the rows of the board, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        (0..self.height()).map(move |y| (0..self.width()).map(move |x| self.get(x, y)))
+    }
+}
+
+impl Index<(usize, usize)> for Board {
+    type Output = Cell;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Cell {
+        match self.get(x, y) {
+            Cell::Floor => &Cell::Floor,
+            Cell::Wall => &Cell::Wall,
+            Cell::Piece => &Cell::Piece,
+            Cell::Goal => &Cell::Goal,
+            Cell::PieceOnGoal => &Cell::PieceOnGoal,
+            Cell::Player => &Cell::Player,
+            Cell::PlayerOnGoal => &Cell::PlayerOnGoal,
+        }
+    }
+}
 
 pub fn print_board(board: &Board) {
-    for row in board {
+    for row in board.rows() {
         for cell in row {
-            print!("{} ", cell_symbol(cell));
+            print!("{} ", cell_symbol(&cell));
         }
         print!("\r\n");
     }
 }
 
+/// Position of the first cell of the given type, in row-major order.
 pub fn find_cell(board: &Board, cell: Cell) -> Option<(u8, u8)> {
-    let size = board.len();
-    for y in 0..size {
-        for x in 0..size {
-            if cell == board[y][x] {
-                return Some((x as u8, y as u8));
-            }
+    for ((x, y), other) in board.iter() {
+        if cell == other {
+            return Some((x as u8, y as u8));
         }
     }
 
@@ -66,11 +295,31 @@ pub fn find_cell(board: &Board, cell: Cell) -> Option<(u8, u8)> {
 
 // pub trait Iter = Iterator<Item = Board>;
 pub trait BaseIter: Iterator<Item = Board> {
-    fn new_base(size: u8) -> Self;
+    /// Iterate over square boards of the given size.
+    fn new_base(size: u8) -> Self where Self: Sized {
+        Self::new_rect(size, size)
+    }
+    /// Iterate over boards of the given width and height.
+    fn new_rect(width: u8, height: u8) -> Self;
+    /// Like new_rect(), but random iterators are seeded for reproducible output.
+    /// Deterministic iterators ignore the seed.
+    fn new_seeded(width: u8, height: u8, _seed: u64) -> Self where Self: Sized {
+        Self::new_rect(width, height)
+    }
 }
 pub trait SecondaryIter: Iterator<Item = Board> {
     fn new_secondary(board: Board) -> Self;
+    /// Like new_secondary(), but random iterators are seeded for reproducible output.
+    /// Deterministic iterators ignore the seed.
+    fn new_secondary_seeded(board: Board, _seed: u64) -> Self where Self: Sized {
+        Self::new_secondary(board)
+    }
+    /// Like new_secondary_seeded(), but fillers place the given number of pieces and as many goals.
+    /// Iterators that don't place pieces ignore the count.
+    fn new_secondary_multi(board: Board, _num_pieces: usize, seed: u64) -> Self where Self: Sized {
+        Self::new_secondary_seeded(board, seed)
+    }
 }
 pub trait WrapperIter<T: Iterator<Item = Board>>: Iterator<Item = Board> {
     fn new_wrapper(source: T) -> Self;
-}
\ No newline at end of file
+}
@@ -0,0 +1,189 @@
+//! Command line parsing shared by all subcommands.
+
+use std::{fmt, fs, io};
+use std::io::Write;
+use std::str::FromStr;
+
+use sokoban_generator::base::MAX_CELLS;
+
+use crate::formats::Format;
+
+/// Reason a command failed. Usage errors exit with status 2, other failures with status 1.
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad command line: unknown option, missing or malformed value.
+    Usage(String),
+    /// The command ran but couldn't complete.
+    Failed(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Failed(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\nRun `sokoban help` for usage.", message),
+            CliError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+pub type CliResult = Result<(), CliError>;
+
+/// Arguments not consumed yet. Options can appear anywhere, in any order.
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: Vec<String>) -> Args {
+        Args { args: args }
+    }
+
+    /// Remove `name` from the arguments. Returns true if it was present.
+    pub fn take_flag(&mut self, name: &str) -> bool {
+        match self.args.iter().position(|arg| arg == name) {
+            Some(idx) => {
+                self.args.remove(idx);
+                return true;
+            },
+            None => return false,
+        }
+    }
+
+    /// Remove `name <value>` from the arguments and parse the value, if present.
+    pub fn take_value<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, CliError> {
+        let idx = match self.args.iter().position(|arg| arg == name) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        if idx + 1 >= self.args.len() {
+            return Err(CliError::Usage(format!("missing value for {}", name)));
+        }
+
+        let value = self.args.remove(idx + 1);
+        self.args.remove(idx);
+        return match value.parse() {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(_) => Err(CliError::Usage(format!("invalid value for {}: {:?}", name, value))),
+        };
+    }
+
+    /// Return the positional arguments left. Fails on unknown options.
+    pub fn finish(self) -> Result<Vec<String>, CliError> {
+        if let Some(option) = self.args.iter().find(|arg| arg.starts_with("--")) {
+            return Err(CliError::Usage(format!("unknown option {}", option)));
+        }
+
+        return Ok(self.args);
+    }
+
+    /// Like finish(), but fails if there are positional arguments too.
+    pub fn finish_empty(self) -> CliResult {
+        let positional = self.finish()?;
+        if let Some(arg) = positional.first() {
+            return Err(CliError::Usage(format!("unexpected argument {:?}", arg)));
+        }
+
+        return Ok(());
+    }
+}
+
+/// Board dimensions, written `<size>` for square boards or `<width>x<height>`.
+#[derive(Clone, Copy)]
+pub struct Size {
+    pub width: u8,
+    pub height: u8,
+}
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Size, String> {
+        let (width, height) = match text.find('x') {
+            Some(idx) => (&text[..idx], &text[idx + 1..]),
+            None => (text, text),
+        };
+        let width: u8 = width.parse().map_err(|_| format!("invalid size {:?}", text))?;
+        let height: u8 = height.parse().map_err(|_| format!("invalid size {:?}", text))?;
+        if width < 2 || height < 2 || width as usize * height as usize > MAX_CELLS {
+            return Err(format!("size {:?} out of range", text));
+        }
+
+        return Ok(Size { width: width, height: height });
+    }
+}
+
+/// Options shared by the generating subcommands.
+pub struct Common {
+    pub size: Size,
+    pub seed: u64,
+    /// Number of boards to produce, if limited.
+    pub count: Option<usize>,
+    pub pieces: usize,
+    /// Use push rules (see sokoban_generator::push) instead of sliding.
+    pub push: bool,
+    pub format: Format,
+    /// File to write to, instead of standard output.
+    pub output: Option<String>,
+}
+
+/// Help text for the options parsed by Common::take(), as a literal for concat!().
+macro_rules! common_help {
+    () => { "    --size <n> | <w>x<h>   Board size [default: 5]
+    --seed <n>             Seed for random generation [default: random]
+    --count <n>            Number of boards to produce
+    --pieces <n>           Number of pieces and goals per board [default: 1]
+    --push                 Classic push rules, with a player, instead of sliding
+    --format <format>      Output format: text, xsb, slc or json [default: text]
+    --output <file>        Write to a file instead of standard output" };
+}
+
+impl Common {
+    /// Parse the shared options, with their defaults.
+    pub fn take(args: &mut Args) -> Result<Common, CliError> {
+        let size = args.take_value::<Size>("--size")?.unwrap_or(Size { width: 5, height: 5 });
+        let pieces = args.take_value::<usize>("--pieces")?.unwrap_or(1);
+        if pieces == 0 {
+            return Err(CliError::Usage("--pieces must be at least 1".to_string()));
+        }
+
+        return Ok(Common {
+            size: size,
+            seed: args.take_value("--seed")?.unwrap_or_else(rand::random),
+            count: args.take_value("--count")?,
+            pieces: pieces,
+            push: args.take_flag("--push"),
+            format: args.take_value("--format")?.unwrap_or(Format::Text),
+            output: args.take_value("--output")?,
+        });
+    }
+}
+
+/// Write text to the given file, or to standard output.
+pub fn write_output(text: &str, output: &Option<String>) -> CliResult {
+    let result = match output {
+        Some(path) => fs::write(path, text),
+        None => io::stdout().write_all(text.as_bytes()),
+    };
+
+    return result.map_err(|error| CliError::Failed(format!("can't write output: {}", error)));
+}
+
+/// Read the given file, or standard input for None or `-`.
+pub fn read_input(input: Option<&str>) -> Result<String, CliError> {
+    let result = match input {
+        Some(path) if path != "-" => fs::read_to_string(path),
+        _ => io::read_to_string(io::stdin()),
+    };
+
+    let name = input.unwrap_or("standard input");
+    return result.map_err(|error| CliError::Failed(format!("can't read {}: {}", name, error)));
+}
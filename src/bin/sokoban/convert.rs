@@ -0,0 +1,36 @@
+//! `sokoban convert`: translate level collections between formats.
+
+use crate::args::{self, Args, CliError, CliResult};
+use crate::formats::{self, Format};
+
+pub const HELP: &str = "\
+Convert a level collection from one format to another.
+
+Usage: sokoban convert [options] [<input>]
+
+Reads standard input if <input> is missing or `-`.
+
+Options:
+    --from <format>        Input format: text, xsb, slc or json [default: from the file extension, or text]
+    --format <format>      Output format [default: text]
+    --output <file>        Write to a file instead of standard output
+
+Text input is one board per paragraph, in native notation. Titles and other metadata are lost when
+converting to text.";
+
+pub fn run(mut args: Args) -> CliResult {
+    let from = args.take_value::<Format>("--from")?;
+    let format = args.take_value("--format")?.unwrap_or(Format::Text);
+    let output = args.take_value("--output")?;
+    let positional = args.finish()?;
+    if positional.len() > 1 {
+        return Err(CliError::Usage(format!("unexpected argument {:?}", positional[1])));
+    }
+
+    let input = positional.first().map(|path| path.as_str());
+    let from = from.unwrap_or_else(|| input.map_or(Format::Text, Format::from_path));
+    let pack = formats::read_pack(&args::read_input(input)?, from)?;
+
+    let text = formats::write_pack(&pack, format)?;
+    return args::write_output(&text, &output);
+}
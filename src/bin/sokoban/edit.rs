@@ -0,0 +1,234 @@
+//! `sokoban edit`: level editor in the terminal.
+
+use std::fs;
+use std::io::stdout;
+use std::path::Path;
+
+use crossterm::ExecutableCommand;
+use crossterm::event::{read, Event, KeyCode};
+use crossterm::terminal::{Clear, ClearType, enable_raw_mode, disable_raw_mode};
+use crossterm::cursor::MoveTo;
+use crossterm::style::Colorize;
+
+use sokoban_generator::base::{Cell, Board, CellSet};
+use sokoban_generator::colorprint::color_cell_symbol;
+use sokoban_generator::text;
+
+use crate::args::{Args, CliError, CliResult, Size};
+use crate::rules::{Rules, dead_cells, explore};
+
+pub const HELP: &str = "\
+Edit a level in the terminal, in native text notation.
+
+Usage: sokoban edit [options] <file>
+
+Opens <file> if it exists, or starts from an empty board saved there.
+
+Options:
+    --size <n> | <w>x<h>   Size of a new board [default: 5]
+
+Keys: arrows to move the cursor, space to toggle wall and floor, b to toggle a box, g to toggle a goal,
+p to place or remove the player (push rules), s to save, l to load the file again, q to quit.
+
+The solver runs after every change. Cells where a lone box could never reach a goal are shown in red.";
+
+pub fn run(mut args: Args) -> CliResult {
+    let size = args.take_value::<Size>("--size")?;
+    let positional = args.finish()?;
+    let path = match positional.as_slice() {
+        [path] => path.clone(),
+        [] => return Err(CliError::Usage("missing file to edit".to_string())),
+        [_, extra, ..] => return Err(CliError::Usage(format!("unexpected argument {:?}", extra))),
+    };
+
+    let mut editor = if Path::new(&path).exists() {
+        Editor::new(load(&path)?)
+    } else {
+        let size = size.unwrap_or(Size { width: 5, height: 5 });
+        let mut board = Board::new(size.width, size.height);
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                board.set(x, y, Cell::Floor);
+            }
+        }
+        Editor::new(board)
+    };
+
+    let mut stdout = stdout();
+    enable_raw_mode().unwrap();
+    stdout.execute(Clear(ClearType::All)).unwrap();
+
+    loop {
+        stdout.execute(MoveTo(0, 0)).unwrap();
+        editor.draw(&path);
+
+        if let Event::Key(event) = read().unwrap() {
+            let (x, y) = editor.cursor;
+            let (width, height) = editor.board.dimensions();
+            match event.code {
+                KeyCode::Up => editor.cursor.1 = y.saturating_sub(1),
+                KeyCode::Down => editor.cursor.1 = (y + 1).min(height - 1),
+                KeyCode::Left => editor.cursor.0 = x.saturating_sub(1),
+                KeyCode::Right => editor.cursor.0 = (x + 1).min(width - 1),
+                KeyCode::Char(' ') => editor.toggle_wall(),
+                KeyCode::Char('b') => editor.toggle_piece(),
+                KeyCode::Char('g') => editor.toggle_goal(),
+                KeyCode::Char('p') => editor.toggle_player(),
+                KeyCode::Char('s') => {
+                    editor.message = match fs::write(&path, text::format_board(&editor.board)) {
+                        Ok(()) => {
+                            editor.modified = false;
+                            format!("Saved {}.", path)
+                        },
+                        Err(error) => format!("Can't save: {}", error),
+                    };
+                },
+                KeyCode::Char('l') => {
+                    match load(&path) {
+                        Ok(board) => {
+                            editor = Editor::new(board);
+                            editor.message = format!("Loaded {}.", path);
+                            stdout.execute(Clear(ClearType::All)).unwrap();
+                        },
+                        Err(error) => editor.message = error.to_string(),
+                    }
+                },
+                KeyCode::Char('q') => break,
+                _ => (),
+            }
+        }
+    }
+
+    disable_raw_mode().unwrap();
+    if editor.modified {
+        eprintln!("sokoban: quit without saving the last changes to {}", path);
+    }
+    return Ok(());
+}
+
+/// Read a single board from a file.
+fn load(path: &str) -> Result<Board, CliError> {
+    let input = fs::read_to_string(path).map_err(|error| CliError::Failed(format!("can't read {}: {}", path, error)))?;
+    return text::parse_board(&input).map_err(|error| CliError::Failed(format!("{}: {}", path, error)));
+}
+
+struct Editor {
+    board: Board,
+    cursor: (usize, usize),
+    /// Changed since it was last saved or loaded.
+    modified: bool,
+    /// Result of the last save or load.
+    message: String,
+    /// Solver results for the current board.
+    status: String,
+    dead: CellSet,
+}
+
+impl Editor {
+    fn new(board: Board) -> Self {
+        let mut editor = Editor {
+            board: board,
+            cursor: (0, 0),
+            modified: false,
+            message: String::new(),
+            status: String::new(),
+            dead: CellSet::default(),
+        };
+        editor.update();
+        return editor;
+    }
+
+    fn cell(&self) -> Cell {
+        return self.board.get(self.cursor.0, self.cursor.1);
+    }
+
+    fn set(&mut self, cell: Cell) {
+        self.board.set(self.cursor.0, self.cursor.1, cell);
+        self.modified = true;
+        self.update();
+    }
+
+    /// Wall to floor, anything else to wall.
+    fn toggle_wall(&mut self) {
+        let cell = if self.cell() == Cell::Wall { Cell::Floor } else { Cell::Wall };
+        self.set(cell);
+    }
+
+    /// Add or remove a box, keeping the goal. The player makes way for it.
+    fn toggle_piece(&mut self) {
+        let cell = match self.cell() {
+            Cell::Piece => Cell::Floor,
+            Cell::PieceOnGoal => Cell::Goal,
+            Cell::Goal | Cell::PlayerOnGoal => Cell::PieceOnGoal,
+            Cell::Floor | Cell::Wall | Cell::Player => Cell::Piece,
+        };
+        self.set(cell);
+    }
+
+    /// Add or remove a goal, keeping what stands on it. Walls become floor.
+    fn toggle_goal(&mut self) {
+        let cell = match self.cell() {
+            Cell::Goal => Cell::Floor,
+            Cell::PieceOnGoal => Cell::Piece,
+            Cell::PlayerOnGoal => Cell::Player,
+            Cell::Floor | Cell::Wall => Cell::Goal,
+            Cell::Piece => Cell::PieceOnGoal,
+            Cell::Player => Cell::PlayerOnGoal,
+        };
+        self.set(cell);
+    }
+
+    /// Move the player here, or remove it if it's already here. Boxes make way for it.
+    fn toggle_player(&mut self) {
+        let cell = match self.cell() {
+            Cell::Player => Cell::Floor,
+            Cell::PlayerOnGoal => Cell::Goal,
+            Cell::Goal | Cell::PieceOnGoal => Cell::PlayerOnGoal,
+            Cell::Floor | Cell::Wall | Cell::Piece => Cell::Player,
+        };
+        self.set(cell);
+    }
+
+    /// Run the solver on the current board.
+    fn update(&mut self) {
+        let rules = Rules::of(&self.board);
+        let (num_pieces, num_goals) = (self.board.piece_set().len(), self.board.goal_set().len());
+        self.dead = dead_cells(&self.board, rules);
+        self.status = if num_pieces == 0 || num_pieces != num_goals {
+            format!("{} boxes for {} goals.", num_pieces, num_goals)
+        } else {
+            let stats = explore(&self.board, rules);
+            if stats.solvable {
+                format!("Solvable in {} moves.", stats.num_moves)
+            } else {
+                "Not solvable.".to_string()
+            }
+        };
+    }
+
+    fn draw(&self, path: &str) {
+        let rules = if Rules::of(&self.board) == Rules::Push { "push" } else { "slide" };
+        print!("------------- EDITOR -------------\r\n");
+        print!("{}{}          \r\n", path, if self.modified { " [modified]" } else { "" });
+        print!("{} to move the cursor.\r\n", "Arrows".yellow());
+        print!("{} wall, {} box, {} goal, {} player.\r\n", "Space".yellow(), "b".yellow(), "g".yellow(), "p".yellow());
+        print!("{} to save, {} to load, {} to quit.\r\n", "s".yellow(), "l".yellow(), "q".yellow());
+        print!("----------------------------------\r\n");
+        print!("Rules: {}. {}                    \r\n", rules, self.status);
+        print!("{}                               \r\n", self.message);
+        print!("----------------------------------\r\n");
+        for y in 0..self.board.height() {
+            for x in 0..self.board.width() {
+                let symbol = color_cell_symbol(&self.board.get(x, y));
+                if (x, y) == self.cursor {
+                    print!("{} ", symbol.on_dark_blue());
+                } else if self.dead.contains(self.board.cell_index(x, y)) {
+                    print!("{} ", symbol.on_dark_red());
+                } else {
+                    print!("{} ", symbol);
+                }
+            }
+            print!("\r\n");
+        }
+    }
+}
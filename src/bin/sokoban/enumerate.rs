@@ -0,0 +1,45 @@
+//! `sokoban enumerate`: list every board of a size, in a fixed order.
+
+use sokoban_generator::base::Board;
+use sokoban_generator::iters::{empty, holistic};
+use sokoban_generator::pack::{Level, Pack};
+
+use crate::args::{self, Args, CliResult, Common};
+use crate::formats;
+use crate::rules::Rules;
+
+pub const HELP: &str = concat!("\
+Enumerate boards exhaustively, skipping rotations and reflections. Solvable boards by default.
+
+Usage: sokoban enumerate [options]
+
+Options:
+", common_help!(), "
+    --all                  Include unsolvable boards
+    --empty                Only the empty boards: walls and floor, without pieces or goals
+
+--seed has no effect: the order is always the same. All boards are listed unless --count is set.");
+
+pub fn run(mut args: Args) -> CliResult {
+    let common = Common::take(&mut args)?;
+    let all = args.take_flag("--all");
+    let only_empty = args.take_flag("--empty");
+    args.finish_empty()?;
+
+    let (width, height, num_pieces) = (common.size.width, common.size.height, common.pieces);
+    let boards: Box<dyn Iterator<Item = Board>> = match (only_empty, all, Rules::from_flag(common.push)) {
+        (true, _, _) => Box::new(empty::serial_rect(width, height)),
+        (false, true, Rules::Slide) => Box::new(holistic::serial_multi(width, height, num_pieces)),
+        (false, true, Rules::Push) => Box::new(holistic::serial_push(width, height, num_pieces)),
+        (false, false, Rules::Slide) => Box::new(holistic::solvable_serial_multi(width, height, num_pieces)),
+        (false, false, Rules::Push) => Box::new(holistic::solvable_serial_push(width, height, num_pieces)),
+    };
+
+    let mut pack = Pack::default();
+    for (idx, board) in boards.take(common.count.unwrap_or(usize::MAX)).enumerate() {
+        pack.levels.push(Level::new(&format!("{}", idx + 1), board));
+    }
+
+    let text = formats::write_pack(&pack, common.format)?;
+    return args::write_output(&text, &common.output);
+}
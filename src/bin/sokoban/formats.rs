@@ -0,0 +1,120 @@
+//! Level collection formats accepted by --format and --from.
+
+use std::iter;
+use std::str::FromStr;
+
+use sokoban_generator::pack::{self, Level, Pack};
+use sokoban_generator::text;
+
+use crate::args::CliError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Native notation (see sokoban_generator::text), boards separated by blank lines. No metadata.
+    Text,
+    /// XSB / .sok level pack (see sokoban_generator::pack).
+    Xsb,
+    /// SLC XML level pack.
+    Slc,
+    /// JSON (see sokoban_generator::json). Only with the serde feature.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Format, String> {
+        match text {
+            "text" => Ok(Format::Text),
+            "xsb" | "sok" => Ok(Format::Xsb),
+            "slc" | "xml" => Ok(Format::Slc),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format {:?}", text)),
+        }
+    }
+}
+
+impl Format {
+    /// Guess the format of a file from its extension. Defaults to Text.
+    pub fn from_path(path: &str) -> Format {
+        let extension = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+        return extension.parse().unwrap_or(Format::Text);
+    }
+}
+
+/// Read a collection of levels.
+pub fn read_pack(input: &str, format: Format) -> Result<Pack, CliError> {
+    let failed = |error: String| CliError::Failed(error);
+    match format {
+        Format::Text => {
+            let mut pack = Pack::default();
+            let mut rows: Vec<&str> = Vec::new();
+            // Index of the first line of the current board, to report errors in the whole input.
+            let mut first_line = 0;
+            // The empty line at the end closes the last board.
+            for (idx, line) in input.lines().chain(iter::once("")).enumerate() {
+                if !line.trim().is_empty() {
+                    if rows.is_empty() {
+                        first_line = idx;
+                    }
+                    rows.push(line);
+                    continue;
+                }
+                if rows.is_empty() {
+                    continue;
+                }
+
+                let board = text::parse_board(&rows.join("\n")).map_err(|mut error| {
+                    error.line += first_line;
+                    failed(error.to_string())
+                })?;
+                let title = format!("{}", pack.levels.len() + 1);
+                pack.levels.push(Level::new(&title, board));
+                rows.clear();
+            }
+            return Ok(pack);
+        },
+        Format::Xsb => return pack::read_sok(input).map_err(|error| failed(error.to_string())),
+        Format::Slc => return pack::read_slc(input).map_err(|error| failed(error.to_string())),
+        Format::Json => return read_json(input),
+    }
+}
+
+/// Write a collection of levels. Always ends with a newline, unless empty.
+pub fn write_pack(pack: &Pack, format: Format) -> Result<String, CliError> {
+    match format {
+        Format::Text => {
+            let boards: Vec<String> = pack.levels.iter().map(|level| text::format_board(&level.board)).collect();
+            return Ok(boards.join("\n"));
+        },
+        Format::Xsb => return Ok(pack::write_sok(pack)),
+        Format::Slc => return Ok(pack::write_slc(pack)),
+        Format::Json => return write_json(pack),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn read_json(input: &str) -> Result<Pack, CliError> {
+    serde_json::from_str(input).map_err(|error| CliError::Failed(format!("invalid JSON: {}", error)))
+}
+
+#[cfg(feature = "serde")]
+fn write_json(pack: &Pack) -> Result<String, CliError> {
+    let json = serde_json::to_string_pretty(pack).map_err(|error| CliError::Failed(error.to_string()))?;
+    return Ok(json + "\n");
+}
+
+#[cfg(not(feature = "serde"))]
+fn read_json(_input: &str) -> Result<Pack, CliError> {
+    Err(no_json())
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_json(_pack: &Pack) -> Result<String, CliError> {
+    Err(no_json())
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn no_json() -> CliError {
+    CliError::Usage("JSON needs the serde feature: build with `--features serde`".to_string())
+}
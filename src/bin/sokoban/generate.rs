@@ -0,0 +1,80 @@
+//! `sokoban generate`: random solvable levels, with their solutions.
+
+use std::ops::RangeInclusive;
+
+use sokoban_generator::base::Board;
+use sokoban_generator::iters::holistic;
+use sokoban_generator::pack::{Level, Pack};
+
+use crate::args::{self, Args, CliError, CliResult, Common};
+use crate::formats;
+use crate::rules::{Rules, explore};
+
+pub const HELP: &str = concat!("\
+Generate random solvable levels, with their shortest solution.
+
+Usage: sokoban generate [options]
+
+Options:
+", common_help!(), "
+    --moves <min>..<max>   Only keep levels whose shortest solution length is in range.
+                           Either bound can be left out; a single number means exactly that many.
+
+The same seed and options always give the same levels. Only one level is made unless --count is set.");
+
+pub fn run(mut args: Args) -> CliResult {
+    let common = Common::take(&mut args)?;
+    let moves_text = args.take_value::<String>("--moves")?;
+    let moves = match &moves_text {
+        Some(text) => parse_range(text)?,
+        None => 0..=usize::MAX,
+    };
+    args.finish_empty()?;
+
+    let (width, height) = (common.size.width, common.size.height);
+    let rules = Rules::from_flag(common.push);
+    let boards: Box<dyn Iterator<Item = Board>> = match rules {
+        Rules::Slide => Box::new(holistic::solvable_random_moves(width, height, common.pieces, common.seed, moves)),
+        Rules::Push => Box::new(holistic::solvable_push_moves(width, height, common.pieces, common.seed, moves)),
+    };
+
+    let count = common.count.unwrap_or(1);
+    let mut pack = Pack::default();
+    for board in boards.take(count) {
+        let stats = explore(&board, rules);
+        let mut level = Level::new(&format!("{}", pack.levels.len() + 1), board);
+        level.num_moves = Some(stats.num_moves);
+        level.solution = Some(stats.solution);
+        pack.levels.push(level);
+    }
+
+    if pack.levels.is_empty() {
+        let moves = match moves_text {
+            Some(text) => format!(" and {} moves", text),
+            None => String::new(),
+        };
+        return Err(CliError::Failed(format!("no {}x{} level with {} pieces{}", width, height, common.pieces, moves)));
+    }
+    if pack.levels.len() < count {
+        eprintln!("sokoban: only found {} of {} levels", pack.levels.len(), count);
+    }
+
+    let text = formats::write_pack(&pack, common.format)?;
+    return args::write_output(&text, &common.output);
+}
+
+/// Parse `<min>..<max>`, `<min>..`, `..<max>` or `<n>`.
+fn parse_range(text: &str) -> Result<RangeInclusive<usize>, CliError> {
+    let invalid = || CliError::Usage(format!("invalid value for --moves: {:?}", text));
+    let (min, max) = match text.find("..") {
+        Some(idx) => (&text[..idx], &text[idx + 2..]),
+        None => (text, text),
+    };
+    let min = if min.is_empty() { 0 } else { min.parse().map_err(|_| invalid())? };
+    let max = if max.is_empty() { usize::MAX } else { max.parse().map_err(|_| invalid())? };
+    if min > max {
+        return Err(CliError::Usage(format!("empty range for --moves: {:?}", text)));
+    }
+
+    return Ok(min..=max);
+}
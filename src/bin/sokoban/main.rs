@@ -0,0 +1,89 @@
+//! `sokoban`: generate, solve, play and convert Sokoban levels.
+
+#[macro_use]
+mod args;
+mod formats;
+mod rules;
+mod generate;
+mod solve;
+mod play;
+mod progress;
+mod replay;
+mod serve;
+mod stats;
+mod enumerate;
+mod convert;
+mod edit;
+
+use std::{env, process};
+
+use crate::args::{Args, CliError, CliResult};
+
+const HELP: &str = "\
+Generate, solve, play and convert Sokoban levels.
+
+Usage: sokoban <command> [options]
+
+Commands:
+    generate    Random solvable levels, with their solutions
+    solve       Solve levels from a file or standard input
+    play        Play in the terminal
+    stats       Compare the board generation pipelines
+    enumerate   List every board of a size
+    convert     Convert level collections between formats
+    edit        Edit a level in the terminal
+    serve       Play headless, with JSON requests and responses on standard input and output
+    help        Show this message, or `sokoban help <command>` for a command's options";
+
+type Command = fn(Args) -> CliResult;
+
+/// Subcommands with their entry point and help text.
+const COMMANDS: [(&str, Command, &str); 8] = [
+    ("generate", generate::run, generate::HELP),
+    ("solve", solve::run, solve::HELP),
+    ("play", play::run, play::HELP),
+    ("stats", stats::run, stats::HELP),
+    ("enumerate", enumerate::run, enumerate::HELP),
+    ("convert", convert::run, convert::HELP),
+    ("edit", edit::run, edit::HELP),
+    ("serve", serve::run, serve::HELP),
+];
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(error) = dispatch(args) {
+        eprintln!("sokoban: {}", error);
+        process::exit(error.exit_code());
+    }
+}
+
+fn dispatch(mut args: Vec<String>) -> CliResult {
+    if args.is_empty() {
+        return Err(CliError::Usage("missing command".to_string()));
+    }
+
+    let name = args.remove(0);
+    if name == "help" || name == "--help" || name == "-h" {
+        let help = match args.first() {
+            Some(command) => find(command)?.2,
+            None => HELP,
+        };
+        println!("{}", help);
+        return Ok(());
+    }
+
+    let (_name, run, help) = find(&name)?;
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", help);
+        return Ok(());
+    }
+
+    return run(Args::new(args));
+}
+
+fn find(name: &str) -> Result<(&'static str, Command, &'static str), CliError> {
+    return COMMANDS.iter()
+        .find(|command| command.0 == name)
+        .copied()
+        .ok_or_else(|| CliError::Usage(format!("unknown command {:?}", name)));
+}
@@ -0,0 +1,453 @@
+//! `sokoban play`: interactive game in the terminal.
+
+use std::io::{stdout, Stdout};
+use std::path::Path;
+
+use crossterm::ExecutableCommand;
+use crossterm::event::{read, Event, KeyCode};
+use crossterm::terminal::{Clear, ClearType, enable_raw_mode, disable_raw_mode};
+use crossterm::cursor::MoveTo;
+use crossterm::style::Colorize;
+
+use sokoban_generator::base::{Cell, Board};
+use sokoban_generator::iters::holistic;
+use sokoban_generator::pack::Pack;
+use sokoban_generator::colorprint::{color_cell_symbol, color_print_board_highlighted};
+use sokoban_generator::play::{Direction, move_piece};
+use sokoban_generator::push::move_player;
+
+use crate::args::{self, Args, CliError, CliResult, Size};
+use crate::formats::{self, Format};
+use crate::progress::Progress;
+use crate::replay::replay;
+use crate::rules::{Rules, explore};
+
+pub const HELP: &str = "\
+Play random solvable levels in the terminal, or the levels of a pack.
+
+Usage: sokoban play [options]
+
+Options:
+    --size <n> | <w>x<h>   Board size [default: 5]
+    --seed <n>             Seed for random generation [default: random]
+    --pieces <n>           Number of boxes and goals per board [default: 1]
+    --push                 Classic push rules, with a player, instead of sliding
+    --pack <file>          Play the levels of a pack instead, chosen from a menu
+    --from <format>        Pack format: text, xsb, slc or json [default: from the file extension, or text]
+    --save <file>          Where to keep track of solved levels [default: <pack file>.save]
+
+Keys: arrows or WASD to move, u to undo, y to redo, r to restart the level, n for the next level, q to quit.
+Press h for a hint, again for more help: the next direction, then where it leads, then the whole solution.
+Press v to watch the solution from where you are, at the same penalty as the last hint.
+Each hint adds to the penalty shown next to your moves.
+
+Pack levels open one at a time: solving a level unlocks the next. Press m to go back to the menu. Levels
+with a player are played with push rules, the others with sliding rules. Records count the moves plus the
+hint penalty.";
+
+/// Penalty for each hint level, in moves: direction, destination, full solution.
+const HINT_PENALTIES: [usize; 3] = [1, 2, 5];
+/// Levels listed at once in the pack menu.
+const MENU_ROWS: usize = 20;
+
+/// Why play_level() returned.
+#[derive(PartialEq)]
+enum Exit {
+    Quit,
+    Next,
+    Menu,
+}
+
+pub fn run(mut args: Args) -> CliResult {
+    let size = args.take_value::<Size>("--size")?.unwrap_or(Size { width: 5, height: 5 });
+    let seed = args.take_value("--seed")?.unwrap_or_else(rand::random);
+    let num_pieces = args.take_value::<usize>("--pieces")?.unwrap_or(1);
+    let rules = Rules::from_flag(args.take_flag("--push"));
+    let pack_path = args.take_value::<String>("--pack")?;
+    let from = args.take_value::<Format>("--from")?;
+    let save_path = args.take_value::<String>("--save")?;
+    args.finish_empty()?;
+    if num_pieces == 0 {
+        return Err(CliError::Usage("--pieces must be at least 1".to_string()));
+    }
+
+    if let Some(pack_path) = pack_path {
+        let from = from.unwrap_or_else(|| Format::from_path(&pack_path));
+        let pack = formats::read_pack(&args::read_input(Some(&pack_path))?, from)?;
+        if pack.levels.is_empty() {
+            return Err(CliError::Failed(format!("no levels in {}", pack_path)));
+        }
+
+        let save_path = save_path.unwrap_or_else(|| format!("{}.save", pack_path));
+        let name = match &pack.title {
+            Some(title) => title.clone(),
+            None => Path::new(&pack_path).file_name().map_or(pack_path.clone(), |name| name.to_string_lossy().into_owned()),
+        };
+        let progress = Progress::load(&save_path, &name)?;
+        return play_pack(&pack, progress, &save_path);
+    }
+
+    let (width, height) = (size.width, size.height);
+    let mut iter: Box<dyn Iterator<Item = Board>> = match rules {
+        Rules::Slide => Box::new(holistic::solvable_random_multi(width, height, num_pieces, seed)),
+        Rules::Push => Box::new(holistic::solvable_random_push(width, height, num_pieces, seed)),
+    };
+
+    let no_level = || CliError::Failed(format!("no {}x{} level with {} pieces", width, height, num_pieces));
+    let board = iter.next().ok_or_else(no_level)?;
+    let mut state = GameState::fresh(board, rules);
+
+    let mut stdout = stdout();
+    enable_raw_mode().unwrap();
+    stdout.execute(Clear(ClearType::All)).unwrap();
+
+    let header = format!("Seed: {}", seed);
+    while play_level(&mut stdout, &mut state, &header) == Exit::Next {
+        if let Some(board) = iter.next() {
+            state = GameState::fresh(board, rules);
+        }
+        stdout.execute(Clear(ClearType::All)).unwrap();
+    }
+
+    disable_raw_mode().unwrap();
+    return Ok(());
+}
+
+/// Level-select menu for a pack, saving progress after every solved level.
+fn play_pack(pack: &Pack, mut progress: Progress, save_path: &str) -> CliResult {
+    let mut stdout = stdout();
+    enable_raw_mode().unwrap();
+
+    let mut selected = progress.unlocked(pack) - 1;
+    let mut message = String::new();
+    'menu: loop {
+        stdout.execute(Clear(ClearType::All)).unwrap();
+        stdout.execute(MoveTo(0, 0)).unwrap();
+        let unlocked = progress.unlocked(pack);
+        draw_menu(pack, &progress, selected, &message);
+
+        let code = match read().unwrap() {
+            Event::Key(event) => event.code,
+            _ => continue,
+        };
+        let start = match code {
+            KeyCode::Up | KeyCode::Char('w') => {
+                selected = selected.saturating_sub(1);
+                false
+            },
+            KeyCode::Down | KeyCode::Char('s') => {
+                selected = (selected + 1).min(pack.levels.len() - 1);
+                false
+            },
+            KeyCode::Enter | KeyCode::Char(' ') => selected < unlocked,
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            _ => false,
+        };
+        if !start {
+            continue;
+        }
+
+        // Play from the selected level until the player goes back to the menu.
+        loop {
+            let level = &pack.levels[selected];
+            let mut state = GameState::fresh(level.board.clone(), Rules::of(&level.board));
+            stdout.execute(Clear(ClearType::All)).unwrap();
+            let header = match progress.best(selected) {
+                Some(best) => format!("Level {} of {}: {}. Record: {}.", selected + 1, pack.levels.len(), level.title, best),
+                None => format!("Level {} of {}: {}.", selected + 1, pack.levels.len(), level.title),
+            };
+            let exit = play_level(&mut stdout, &mut state, &header);
+
+            // Hints count against the record, as shown next to the moves.
+            let score = state.position.current_moves + state.penalty;
+            if state.victory && progress.record(selected, score) {
+                message = match progress.save(save_path) {
+                    Ok(()) => format!("New record for {}: {} moves.", level.title, score),
+                    Err(error) => error.to_string(),
+                };
+            }
+
+            match exit {
+                Exit::Quit => break 'menu,
+                Exit::Menu => break,
+                Exit::Next if selected + 1 < progress.unlocked(pack) => selected += 1,
+                Exit::Next => break,
+            }
+        }
+    }
+
+    disable_raw_mode().unwrap();
+    return Ok(());
+}
+
+fn draw_menu(pack: &Pack, progress: &Progress, selected: usize, message: &str) {
+    let unlocked = progress.unlocked(pack);
+    print!("---------------- {} ----------------\r\n", pack.title.as_deref().unwrap_or("LEVELS"));
+    print!("{} to choose, {} to play, {} to quit.\r\n", "Arrows".yellow(), "Enter".yellow(), "q".yellow());
+    print!("Solved: {} of {}.\r\n", (0..pack.levels.len()).filter(|&level| progress.best(level).is_some()).count(), pack.levels.len());
+    print!("{}\r\n", message);
+    print!("--------------------------------------\r\n");
+
+    let first = selected.saturating_sub(MENU_ROWS / 2).min(pack.levels.len().saturating_sub(MENU_ROWS));
+    for (idx, level) in pack.levels.iter().enumerate().skip(first).take(MENU_ROWS) {
+        let marker = if idx == selected { ">" } else { " " };
+        let status = match (progress.best(idx), level.num_moves) {
+            _ if idx >= unlocked => "locked".dark_grey().to_string(),
+            (Some(best), Some(optimal)) => format!("{}", format!("best {} of {}", best, optimal).green()),
+            (Some(best), None) => format!("{}", format!("best {}", best).green()),
+            (None, _) => "open".yellow().to_string(),
+        };
+        print!("{} {:>3}. {:<24} {}\r\n", marker, idx + 1, level.title, status);
+    }
+}
+
+/// Play a level until the player quits or asks for another.
+fn play_level(stdout: &mut Stdout, state: &mut GameState, header: &str) -> Exit {
+    loop {
+        draw(stdout, state, header, &state.position.board, &state.hint_cells());
+
+        if let Event::Key(event) = read().unwrap() {
+            match event.code {
+                KeyCode::Up    | KeyCode::Char('w') => state.play(Direction::Up),
+                KeyCode::Left  | KeyCode::Char('a') => state.play(Direction::Left),
+                KeyCode::Down  | KeyCode::Char('s') => state.play(Direction::Down),
+                KeyCode::Right | KeyCode::Char('d') => state.play(Direction::Right),
+                KeyCode::Char('u') => state.undo(),
+                KeyCode::Char('y') => state.redo(),
+                KeyCode::Char('r') => state.restart(),
+                KeyCode::Char('h') | KeyCode::Char('v') => {
+                    if event.code == KeyCode::Char('v') {
+                        state.reveal();
+                    } else {
+                        state.hint();
+                    }
+                    if state.hint_level == HINT_PENALTIES.len() {
+                        replay(stdout, "Solution from the current position.", &state.position.board, &state.solution, state.rules);
+                    }
+                },
+                KeyCode::Char('n') => return Exit::Next,
+                KeyCode::Char('m') => return Exit::Menu,
+                KeyCode::Char('q') => return Exit::Quit,
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Draw the HUD for the current state, followed by the given board.
+fn draw(stdout: &mut Stdout, state: &GameState, header: &str, board: &Board, highlighted: &[(u8, u8)]) {
+    stdout.execute(MoveTo(0, 0)).unwrap();
+    let round_display = format!("{:02}", state.position.round).yellow();
+    print!("------------- [{}] -------------\r\n", round_display);
+    print!("{}\r\n", header);
+    print!("{} to move.\r\n", "Arrows".yellow());
+    print!("{} to quit.\r\n", "q".yellow());
+    print!("{} to undo, {} to redo.\r\n", "u".yellow(), "y".yellow());
+    print!("{} to restart, {} for the next level.\r\n", "r".yellow(), "n".yellow());
+    print!("{} for a hint, {} to show the solution.\r\n", "h".yellow(), "v".yellow());
+    print!("{}: box; {}: goal; {}: box on goal.\r\n", color_cell_symbol(&Cell::Piece), color_cell_symbol(&Cell::Goal), color_cell_symbol(&Cell::PieceOnGoal));
+    if state.rules == Rules::Push {
+        print!("{}: you. Push boxes one cell at a time.\r\n", color_cell_symbol(&Cell::Player));
+    }
+    print!("--------------------------------\r\n");
+    if state.victory {
+        print!("VICTORY!                    \r\n");
+    } else if state.position.old_pos != state.position.new_pos {
+        print!("{} -> {}        \r\n", format_positions(&state.position.old_pos), format_positions(&state.position.new_pos));
+    } else {
+        print!("Didn't move.                \r\n");
+    }
+    print!("--------------------------------\r\n");
+    print!("Best:  {}   \r\n", state.best_moves);
+    print!("Moves: {}. ", state.position.current_moves);
+    if state.victory || state.can_win {
+        print!("Remaining: {}.", state.remaining_moves);
+    } else {
+        print!("[CAN'T WIN]");
+    }
+    print!("      \r\n");
+    print!("Hints: {}. Penalty: +{}.      \r\n", state.hints_used, state.penalty);
+    match (state.hint_level, state.solution.first()) {
+        (0, _) => print!("                                \r\n"),
+        (_, None) => print!("{}           \r\n", "No solution from here.".yellow()),
+        (1, Some(direction)) => print!("Hint: {}.                       \r\n", format!("{:?}", direction).yellow()),
+        (2, Some(direction)) => print!("Hint: {}, to the marked cells.  \r\n", format!("{:?}", direction).yellow()),
+        (_, Some(_)) => print!("Hint: {} to watch it again.     \r\n", "h".yellow()),
+    }
+    println!("--------------------------------\r\n");
+    color_print_board_highlighted(board, highlighted);
+}
+
+/// Everything that changes with a move, so it can be undone.
+#[derive(Clone)]
+struct Position {
+    board: Board,
+    /// Key presses that led here, moving or not.
+    round: usize,
+    old_pos: Vec<(u8, u8)>,
+    new_pos: Vec<(u8, u8)>,
+    /// Moves that changed the board.
+    current_moves: usize,
+}
+
+struct GameState {
+    rules: Rules,
+    /// Where the level started, for restarts.
+    start: Board,
+    position: Position,
+    /// Positions before each move, most recent last.
+    history: Vec<Position>,
+    /// Positions undone, most recent last. Cleared by any new move.
+    undone: Vec<Position>,
+    best_moves: usize,
+    remaining_moves: usize,
+    /// Shortest solution from the current position, empty if there's none.
+    solution: Vec<Direction>,
+    can_win: bool,
+    victory: bool,
+    /// How much help was asked for in the current position, up to HINT_PENALTIES.len().
+    hint_level: usize,
+    /// Hints asked for in this level, and the moves they cost.
+    hints_used: usize,
+    penalty: usize,
+}
+
+impl GameState {
+    fn fresh(board: Board, rules: Rules) -> Self {
+        let pos = tracked_positions(&board, rules);
+        let stats = explore(&board, rules);
+        return GameState {
+            rules: rules,
+            start: board.clone(),
+            position: Position {
+                board: board,
+                round: 0,
+                old_pos: pos.clone(),
+                new_pos: pos,
+                current_moves: 0,
+            },
+            history: Vec::new(),
+            undone: Vec::new(),
+            best_moves: stats.num_moves,
+            remaining_moves: stats.num_moves,
+            solution: stats.solution,
+            can_win: stats.solvable,
+            victory: false,
+            hint_level: 0,
+            hints_used: 0,
+            penalty: 0,
+        };
+    }
+
+    fn play(&mut self, direction: Direction) {
+        if self.victory {
+            return;
+        }
+
+        let previous = self.position.clone();
+        let position = &mut self.position;
+        position.round += 1;
+
+        let positions = match self.rules {
+            Rules::Slide => move_piece(&mut position.board, direction).unwrap().piece_positions,
+            Rules::Push => vec![move_player(&mut position.board, direction).unwrap().player_pos],
+        };
+        position.old_pos = std::mem::replace(&mut position.new_pos, positions);
+        if position.old_pos != position.new_pos {
+            position.current_moves += 1;
+        }
+        if position.board == previous.board {
+            // Nothing to undo, and the moves undone can still be replayed.
+            return;
+        }
+
+        self.history.push(previous);
+        self.undone.clear();
+        self.update();
+    }
+
+    /// Go back to the position before the last move, if any.
+    fn undo(&mut self) {
+        if let Some(previous) = self.history.pop() {
+            self.undone.push(std::mem::replace(&mut self.position, previous));
+            self.update();
+        }
+    }
+
+    /// Replay the last move undone, if any.
+    fn redo(&mut self) {
+        if let Some(next) = self.undone.pop() {
+            self.history.push(std::mem::replace(&mut self.position, next));
+            self.update();
+        }
+    }
+
+    /// Start the same level over. Can't be undone, and keeps the hint penalty.
+    fn restart(&mut self) {
+        let (hints_used, penalty) = (self.hints_used, self.penalty);
+        *self = GameState::fresh(self.start.clone(), self.rules);
+        self.hints_used = hints_used;
+        self.penalty = penalty;
+    }
+
+    /// Give more help for the current position. Only the first request at each level is counted.
+    fn hint(&mut self) {
+        if self.victory || self.hint_level == HINT_PENALTIES.len() {
+            return;
+        }
+        if self.solution.is_empty() {
+            // Free: only says there's nothing to find.
+            self.hint_level = 1;
+            return;
+        }
+
+        self.penalty += HINT_PENALTIES[self.hint_level];
+        self.hints_used += 1;
+        self.hint_level += 1;
+    }
+
+    /// Skip to the last hint level, paying for the levels skipped.
+    fn reveal(&mut self) {
+        while !self.victory && !self.solution.is_empty() && self.hint_level < HINT_PENALTIES.len() {
+            self.hint();
+        }
+    }
+
+    /// Cells to mark for the current hint: where the next optimal move leads.
+    fn hint_cells(&self) -> Vec<(u8, u8)> {
+        let direction = match self.solution.first() {
+            Some(direction) if self.hint_level >= 2 => *direction,
+            _ => return Vec::new(),
+        };
+
+        let mut board = self.position.board.clone();
+        match self.rules {
+            Rules::Slide => { move_piece(&mut board, direction); },
+            Rules::Push => { move_player(&mut board, direction); },
+        }
+        return tracked_positions(&board, self.rules);
+    }
+
+    /// Recompute what depends on the current board.
+    fn update(&mut self) {
+        let explore_stats = explore(&self.position.board, self.rules);
+        self.victory = self.position.board.is_solved();
+        self.can_win = explore_stats.solvable;
+        self.remaining_moves = explore_stats.num_moves;
+        self.solution = explore_stats.solution;
+        self.hint_level = 0;
+    }
+}
+
+/// Positions shown in the HUD: the boxes when sliding, the player when pushing.
+fn tracked_positions(board: &Board, rules: Rules) -> Vec<(u8, u8)> {
+    match rules {
+        Rules::Slide => board.pieces(),
+        Rules::Push => board.player().into_iter().collect(),
+    }
+}
+
+fn format_positions(positions: &[(u8, u8)]) -> String {
+    let strings: Vec<String> = positions.iter().map(|(x, y)| format!("({}, {})", x, y)).collect();
+    return strings.join(" ");
+}
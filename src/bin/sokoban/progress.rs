@@ -0,0 +1,91 @@
+//! Save files for level packs: which levels are solved, and in how few moves.
+//!
+//! One line per solved level, with the best score (moves plus hint penalty), the level number in its
+//! pack and the pack name separated by tabs (shown as `\t`):
+//!
+//! ```text
+//! # sokoban progress
+//! 7\t1\tMicroban
+//! 12\t2\tMicroban
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored. Several packs can share a save file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+
+use sokoban_generator::pack::Pack;
+
+use crate::args::CliError;
+
+/// Best move count of each solved level of a pack, by index. Keeps the other packs of the save
+/// file as they were.
+pub struct Progress {
+    /// Name of the pack being played.
+    pack: String,
+    best: HashMap<(String, usize), usize>,
+}
+
+impl Progress {
+    /// Read a save file for the pack named `pack`. A missing file means nothing was solved yet.
+    pub fn load(path: &str, pack: &str) -> Result<Progress, CliError> {
+        let mut progress = Progress { pack: pack.to_string(), best: HashMap::new() };
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(progress),
+            Err(error) => return Err(CliError::Failed(format!("can't read {}: {}", path, error))),
+        };
+
+        for (idx, line) in input.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || CliError::Failed(format!("{}:{}: invalid progress line {:?}", path, idx + 1, line));
+            let mut fields = line.splitn(3, '\t');
+            let moves = fields.next().and_then(|moves| moves.parse().ok()).ok_or_else(invalid)?;
+            let number: usize = fields.next().and_then(|number| number.parse().ok()).ok_or_else(invalid)?;
+            let pack = fields.next().ok_or_else(invalid)?;
+            if number == 0 {
+                return Err(invalid());
+            }
+            progress.best.insert((pack.to_string(), number - 1), moves);
+        }
+
+        return Ok(progress);
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), CliError> {
+        let mut levels: Vec<&(String, usize)> = self.best.keys().collect();
+        levels.sort();
+
+        let mut out = String::from("# sokoban progress\n");
+        for level in levels {
+            out.push_str(&format!("{}\t{}\t{}\n", self.best[level], level.1 + 1, level.0));
+        }
+
+        return fs::write(path, out).map_err(|error| CliError::Failed(format!("can't write {}: {}", path, error)));
+    }
+
+    /// Best move count for a level of the pack, if it was solved.
+    pub fn best(&self, level: usize) -> Option<usize> {
+        return self.best.get(&(self.pack.clone(), level)).copied();
+    }
+
+    /// Record a solution. Returns true if it beats the previous best.
+    pub fn record(&mut self, level: usize, moves: usize) -> bool {
+        if self.best(level).is_some_and(|best| best <= moves) {
+            return false;
+        }
+
+        self.best.insert((self.pack.clone(), level), moves);
+        return true;
+    }
+
+    /// Number of levels open to play: the first one, plus one after each solved level in sequence.
+    pub fn unlocked(&self, pack: &Pack) -> usize {
+        let solved = (0..pack.levels.len()).take_while(|&level| self.best(level).is_some()).count();
+        return (solved + 1).min(pack.levels.len());
+    }
+}
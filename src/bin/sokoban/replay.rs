@@ -0,0 +1,158 @@
+//! Animated solution playback in the terminal, shared by `sokoban solve --replay` and `sokoban play`.
+
+use std::io::Stdout;
+use std::time::Duration;
+
+use crossterm::ExecutableCommand;
+use crossterm::event::{poll, read, Event, KeyCode};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::cursor::MoveTo;
+use crossterm::style::Colorize;
+
+use sokoban_generator::base::{Board, CellSet};
+use sokoban_generator::colorprint::color_print_board;
+use sokoban_generator::play::{Direction, move_piece};
+use sokoban_generator::push::move_player;
+use sokoban_generator::text;
+
+use crate::rules::Rules;
+
+/// Pause between frames at each speed, slowest first.
+const SPEEDS: [u64; 5] = [800, 400, 200, 100, 50];
+const DEFAULT_SPEED: usize = 2;
+
+/// One step of the animation.
+struct Frame {
+    board: Board,
+    /// Moves of the solution completed when showing this frame.
+    moves_done: usize,
+}
+
+/// Animate the solution of a board until the user leaves.
+///
+/// Expects the terminal to be in raw mode already, and leaves it cleared.
+pub fn replay(stdout: &mut Stdout, title: &str, board: &Board, solution: &[Direction], rules: Rules) {
+    let frames = frames(board, solution, rules);
+    let mut idx = 0;
+    let mut speed = DEFAULT_SPEED;
+    let mut paused = false;
+
+    stdout.execute(Clear(ClearType::All)).unwrap();
+    loop {
+        let frame = &frames[idx];
+        let finished = idx + 1 == frames.len();
+        stdout.execute(MoveTo(0, 0)).unwrap();
+        print!("-------------- REPLAY --------------\r\n");
+        print!("{}\r\n", title);
+        print!("Solution: {}\r\n", text::format_moves(solution).yellow());
+        print!("Move {} of {}. Speed {} of {}.      \r\n", frame.moves_done, solution.len(), speed + 1, SPEEDS.len());
+        print!("{} to pause, {} to step, {} to change speed.\r\n", "Space".yellow(), "Arrows".yellow(), "+/-".yellow());
+        print!("{} to watch again, {} to leave.\r\n", "r".yellow(), "q".yellow());
+        print!("------------------------------------\r\n");
+        if finished {
+            print!("Done.                 \r\n");
+        } else if paused {
+            print!("Paused.               \r\n");
+        } else {
+            print!("Playing...            \r\n");
+        }
+        print!("------------------------------------\r\n");
+        color_print_board(&frame.board);
+
+        // Wait for a key while playing, but only as long as a frame lasts.
+        let timeout = Duration::from_millis(SPEEDS[speed]);
+        if paused || finished || poll(timeout).unwrap() {
+            if let Event::Key(event) = read().unwrap() {
+                match event.code {
+                    KeyCode::Char(' ') => paused = !paused,
+                    KeyCode::Right => {
+                        paused = true;
+                        idx = (idx + 1).min(frames.len() - 1);
+                    },
+                    KeyCode::Left => {
+                        paused = true;
+                        idx = idx.saturating_sub(1);
+                    },
+                    KeyCode::Char('+') | KeyCode::Char('=') => speed = (speed + 1).min(SPEEDS.len() - 1),
+                    KeyCode::Char('-') => speed = speed.saturating_sub(1),
+                    KeyCode::Char('r') => {
+                        idx = 0;
+                        paused = false;
+                    },
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    _ => (),
+                }
+            }
+        } else {
+            idx += 1;
+        }
+    }
+
+    stdout.execute(Clear(ClearType::All)).unwrap();
+}
+
+/// Every board shown while playing the solution, starting with the given one.
+///
+/// Sliding pieces advance one cell per frame, so a long slide takes several frames.
+fn frames(board: &Board, solution: &[Direction], rules: Rules) -> Vec<Frame> {
+    let mut frames = vec![Frame { board: board.clone(), moves_done: 0 }];
+    let mut board = board.clone();
+    for (idx, direction) in solution.iter().enumerate() {
+        let before = board.clone();
+        match rules {
+            Rules::Slide => { move_piece(&mut board, *direction); },
+            Rules::Push => { move_player(&mut board, *direction); },
+        }
+
+        if rules == Rules::Slide {
+            for pieces in slide(&before, &board, *direction) {
+                let mut frame = before.clone();
+                frame.set_pieces(pieces);
+                frames.push(Frame { board: frame, moves_done: idx });
+            }
+        }
+        frames.push(Frame { board: board.clone(), moves_done: idx + 1 });
+    }
+
+    return frames;
+}
+
+/// Piece layouts between two boards, one cell at a time, excluding both ends.
+fn slide(before: &Board, after: &Board, direction: Direction) -> Vec<CellSet> {
+    let paths = paths(&before.pieces(), &after.pieces(), direction);
+    let longest = paths.iter().map(|&(_, length)| length).max().unwrap_or(0);
+    let (dx, dy) = direction.delta();
+
+    let mut layouts = Vec::new();
+    for step in 1..longest {
+        let mut pieces = CellSet::default();
+        for &((x, y), length) in &paths {
+            let cells = step.min(length) as i32;
+            let (x, y) = (x as i32 + dx * cells, y as i32 + dy * cells);
+            pieces.insert(before.cell_index(x as usize, y as usize));
+        }
+        layouts.push(pieces);
+    }
+
+    return layouts;
+}
+
+/// Start position and number of cells travelled by each piece.
+///
+/// Pieces move along their row or column and can't overtake each other, so sorting both ends by
+/// line, then by position along the line, pairs each start with its end.
+fn paths(starts: &[(u8, u8)], ends: &[(u8, u8)], direction: Direction) -> Vec<((u8, u8), usize)> {
+    let key = |&(x, y): &(u8, u8)| match direction {
+        Direction::Up | Direction::Down => (x, y),
+        Direction::Left | Direction::Right => (y, x),
+    };
+    let mut starts = starts.to_vec();
+    let mut ends = ends.to_vec();
+    starts.sort_by_key(key);
+    ends.sort_by_key(key);
+
+    return starts.into_iter().zip(ends).map(|(start, end)| {
+        let length = (start.0 as i32 - end.0 as i32).abs() + (start.1 as i32 - end.1 as i32).abs();
+        (start, length as usize)
+    }).collect();
+}
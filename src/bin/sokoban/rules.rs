@@ -0,0 +1,42 @@
+//! Choice between the sliding rules (sokoban_generator::play) and push rules (sokoban_generator::push).
+
+use sokoban_generator::base::{Board, CellSet};
+use sokoban_generator::play::{self, ExploreStats};
+use sokoban_generator::push;
+
+/// Which set of rules levels are played with.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rules {
+    /// Boxes slide until they hit something.
+    Slide,
+    /// A player walks around and pushes boxes.
+    Push,
+}
+
+impl Rules {
+    /// Push rules if `push` is set, sliding otherwise.
+    pub fn from_flag(push: bool) -> Rules {
+        if push { Rules::Push } else { Rules::Slide }
+    }
+
+    /// The rules a board is meant for: push rules if it has a player.
+    pub fn of(board: &Board) -> Rules {
+        return Rules::from_flag(board.player().is_some());
+    }
+}
+
+/// Solve a board under the given rules.
+pub fn explore(board: &Board, rules: Rules) -> ExploreStats {
+    match rules {
+        Rules::Slide => play::explore_space(board),
+        Rules::Push => push::explore_space(board),
+    }
+}
+
+/// Cells where a lone piece can't reach a goal under the given rules.
+pub fn dead_cells(board: &Board, rules: Rules) -> CellSet {
+    match rules {
+        Rules::Slide => play::dead_cells(board),
+        Rules::Push => push::dead_cells(board),
+    }
+}
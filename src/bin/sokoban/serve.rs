@@ -0,0 +1,196 @@
+//! `sokoban serve`: headless game driven by JSON lines, for bots and other frontends.
+
+#[cfg(feature = "serde")]
+pub use self::protocol::run;
+
+use crate::args::{Args, CliResult};
+
+pub const HELP: &str = r##"Play a level headless: read one JSON request per line on standard input, and answer each with one
+JSON line on standard output. Boards, cells and directions follow the schema of the library's json
+module. Needs the serde feature.
+
+Usage: sokoban serve
+
+Requests:
+    {"command": "load", "board": {"width": 3, "height": 1, "rows": ["#.@"]}}
+        Start a level. Boards with a player are played with push rules, the others with sliding rules.
+    {"command": "move", "direction": "Right"}
+        Move in a direction: "Up", "Right", "Down" or "Left".
+    {"command": "undo"}
+        Take back the last move that changed the board.
+    {"command": "state"}
+        Show the current state again.
+    {"command": "hint"}
+        Show the shortest solution from the current state.
+
+Responses:
+    {"type": "state", "board": {...}, "pieces": [[2, 0]], "player": null, "moves": 1,
+     "victory": true, "solvable": true, "remaining_moves": 0}
+        After load, move, undo and state. Positions are [x, y]; "player" is null with sliding rules.
+        "moves" counts the moves that changed the board. "remaining_moves" is the length of the
+        shortest solution from here, or null if the level can't be won anymore.
+    {"type": "hint", "direction": "Right", "solution": ["Right"]}
+        After hint. "direction" is the first move of "solution", or null if there is none.
+    {"type": "error", "message": "no board loaded"}
+        After a request that can't be carried out. The state doesn't change.
+
+Moving after a victory is an error. Blank lines are ignored, and the command ends with its input."##;
+
+#[cfg(not(feature = "serde"))]
+pub fn run(args: Args) -> CliResult {
+    args.finish_empty()?;
+    return Err(crate::formats::no_json());
+}
+
+#[cfg(feature = "serde")]
+mod protocol {
+    use std::io::{self, BufRead, Write};
+
+    use serde::{Serialize, Deserialize};
+
+    use sokoban_generator::base::Board;
+    use sokoban_generator::play::{Direction, move_piece};
+    use sokoban_generator::push::move_player;
+
+    use super::{Args, CliResult};
+    use crate::args::CliError;
+    use crate::rules::{Rules, explore};
+
+    #[derive(Deserialize)]
+    #[serde(tag = "command", rename_all = "snake_case")]
+    enum Request {
+        Load { board: Board },
+        Move { direction: Direction },
+        Undo,
+        State,
+        Hint,
+    }
+
+    #[derive(Serialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Response {
+        State(StateUpdate),
+        Hint { direction: Option<Direction>, solution: Vec<Direction> },
+        Error { message: String },
+    }
+
+    #[derive(Serialize)]
+    struct StateUpdate {
+        board: Board,
+        pieces: Vec<(u8, u8)>,
+        player: Option<(u8, u8)>,
+        moves: usize,
+        victory: bool,
+        solvable: bool,
+        remaining_moves: Option<usize>,
+    }
+
+    /// Level being played.
+    struct Game {
+        rules: Rules,
+        /// Every board reached so far, starting with the loaded one. The last one is current.
+        history: Vec<Board>,
+    }
+
+    impl Game {
+        fn new(board: Board) -> Self {
+            return Game { rules: Rules::of(&board), history: vec![board] };
+        }
+
+        fn board(&self) -> &Board {
+            return self.history.last().unwrap();
+        }
+
+        fn play(&mut self, direction: Direction) -> Result<(), String> {
+            if self.board().is_solved() {
+                return Err("level already solved".to_string());
+            }
+
+            let mut board = self.board().clone();
+            match self.rules {
+                Rules::Slide => { move_piece(&mut board, direction); },
+                Rules::Push => { move_player(&mut board, direction); },
+            }
+            if board != *self.board() {
+                self.history.push(board);
+            }
+            return Ok(());
+        }
+
+        fn undo(&mut self) -> Result<(), String> {
+            if self.history.len() == 1 {
+                return Err("nothing to undo".to_string());
+            }
+
+            self.history.pop();
+            return Ok(());
+        }
+
+        fn state(&self) -> Response {
+            let board = self.board();
+            let stats = explore(board, self.rules);
+            return Response::State(StateUpdate {
+                board: board.clone(),
+                pieces: board.pieces(),
+                player: board.player(),
+                moves: self.history.len() - 1,
+                victory: board.is_solved(),
+                solvable: stats.solvable,
+                remaining_moves: if stats.solvable { Some(stats.num_moves) } else { None },
+            });
+        }
+
+        fn hint(&self) -> Response {
+            let stats = explore(self.board(), self.rules);
+            return Response::Hint { direction: stats.solution.first().copied(), solution: stats.solution };
+        }
+    }
+
+    pub fn run(args: Args) -> CliResult {
+        args.finish_empty()?;
+
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        let mut game: Option<Game> = None;
+        for line in stdin.lock().lines() {
+            let line = line.map_err(|error| CliError::Failed(format!("can't read standard input: {}", error)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => answer(&mut game, request),
+                Err(error) => Response::Error { message: format!("invalid request: {}", error) },
+            };
+            let json = serde_json::to_string(&response).map_err(|error| CliError::Failed(error.to_string()))?;
+            writeln!(stdout, "{}", json)
+                .and_then(|()| stdout.flush())
+                .map_err(|error| CliError::Failed(format!("can't write output: {}", error)))?;
+        }
+
+        return Ok(());
+    }
+
+    fn answer(game: &mut Option<Game>, request: Request) -> Response {
+        if let Request::Load { board } = request {
+            return game.insert(Game::new(board)).state();
+        }
+
+        let game = match game {
+            Some(game) => game,
+            None => return Response::Error { message: "no board loaded".to_string() },
+        };
+        let result = match request {
+            Request::Move { direction } => game.play(direction),
+            Request::Undo => game.undo(),
+            Request::Hint => return game.hint(),
+            Request::Load { .. } | Request::State => Ok(()),
+        };
+
+        return match result {
+            Ok(()) => game.state(),
+            Err(message) => Response::Error { message: message },
+        };
+    }
+}
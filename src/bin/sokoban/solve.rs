@@ -0,0 +1,140 @@
+//! `sokoban solve`: solve levels read from a file or standard input.
+
+use std::io::stdout;
+
+use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
+
+use sokoban_generator::pack::Pack;
+use sokoban_generator::play::Direction;
+use sokoban_generator::text;
+
+use crate::args::{self, Args, CliError, CliResult};
+use crate::formats::{self, Format};
+use crate::replay::replay;
+use crate::rules::{Rules, explore};
+
+pub const HELP: &str = "\
+Solve every level of a collection and report the shortest solution of each.
+
+Usage: sokoban solve [options] [<input>]
+
+Reads standard input if <input> is missing or `-`. Levels with a player are solved with push rules,
+the others with sliding rules.
+
+Options:
+    --from <format>        Input format: text, xsb, slc or json [default: from the file extension, or text]
+    --format <format>      Output format: text or json [default: text]
+    --output <file>        Write to a file instead of standard output
+    --replay               Animate each solution in the terminal before writing the results.
+                           Needs a file for <input>, since the keyboard is read from standard input.
+
+JSON output is an array with one object per level. Unsolvable levels have null moves and solution:
+
+    [{ \"title\": \"1\", \"solvable\": true, \"num_moves\": 4, \"solution\": \"URDL\" }]
+
+Exits with status 1 if any level is not solvable.";
+
+/// Result of solving one level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct Report {
+    title: String,
+    solvable: bool,
+    num_moves: Option<usize>,
+    /// Moves in the notation of text::format_moves().
+    solution: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    moves: Vec<Direction>,
+}
+
+pub fn run(mut args: Args) -> CliResult {
+    let from = args.take_value::<Format>("--from")?;
+    let format = args.take_value("--format")?.unwrap_or(Format::Text);
+    let output = args.take_value("--output")?;
+    let animate = args.take_flag("--replay");
+    let positional = args.finish()?;
+    if positional.len() > 1 {
+        return Err(CliError::Usage(format!("unexpected argument {:?}", positional[1])));
+    }
+    if format != Format::Text && format != Format::Json {
+        return Err(CliError::Usage("solve only writes text or json".to_string()));
+    }
+
+    let input = positional.first().map(|path| path.as_str());
+    if animate && input.map_or(true, |path| path == "-") {
+        return Err(CliError::Usage("--replay needs an input file".to_string()));
+    }
+    let from = from.unwrap_or_else(|| input.map_or(Format::Text, Format::from_path));
+    let pack = formats::read_pack(&args::read_input(input)?, from)?;
+    if pack.levels.is_empty() {
+        return Err(CliError::Failed("no levels to solve".to_string()));
+    }
+
+    let reports = solve_pack(&pack);
+    if animate {
+        replay_pack(&pack, &reports);
+    }
+    let out = match format {
+        Format::Json => write_json(&reports)?,
+        _ => write_text(&reports),
+    };
+    args::write_output(&out, &output)?;
+
+    let unsolvable = reports.iter().filter(|report| !report.solvable).count();
+    if unsolvable > 0 {
+        return Err(CliError::Failed(format!("{} of {} levels not solvable", unsolvable, reports.len())));
+    }
+
+    return Ok(());
+}
+
+fn solve_pack(pack: &Pack) -> Vec<Report> {
+    let mut reports = Vec::new();
+    for level in &pack.levels {
+        let stats = explore(&level.board, Rules::of(&level.board));
+        reports.push(Report {
+            title: level.title.clone(),
+            solvable: stats.solvable,
+            num_moves: if stats.solvable { Some(stats.num_moves) } else { None },
+            solution: if stats.solvable { Some(text::format_moves(&stats.solution)) } else { None },
+            moves: stats.solution,
+        });
+    }
+
+    return reports;
+}
+
+/// Animate the solutions of the solvable levels, one after another.
+fn replay_pack(pack: &Pack, reports: &[Report]) {
+    let mut stdout = stdout();
+    enable_raw_mode().unwrap();
+    for (level, report) in pack.levels.iter().zip(reports) {
+        if report.solvable {
+            let title = format!("Level {}", level.title);
+            replay(&mut stdout, &title, &level.board, &report.moves, Rules::of(&level.board));
+        }
+    }
+    disable_raw_mode().unwrap();
+}
+
+fn write_text(reports: &[Report]) -> String {
+    let mut out = String::new();
+    for report in reports {
+        match (&report.num_moves, &report.solution) {
+            (Some(num_moves), Some(solution)) => out.push_str(&format!("{}: solvable in {} moves: {}\n", report.title, num_moves, solution)),
+            _ => out.push_str(&format!("{}: not solvable\n", report.title)),
+        }
+    }
+
+    return out;
+}
+
+#[cfg(feature = "serde")]
+fn write_json(reports: &[Report]) -> Result<String, CliError> {
+    let json = serde_json::to_string_pretty(reports).map_err(|error| CliError::Failed(error.to_string()))?;
+    return Ok(json + "\n");
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_json(_reports: &[Report]) -> Result<String, CliError> {
+    Err(formats::no_json())
+}
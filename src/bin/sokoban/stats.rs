@@ -0,0 +1,131 @@
+//! `sokoban stats`: compare the board pipelines.
+
+use std::{f64, fmt, time};
+
+use sokoban_generator::base::Board;
+use sokoban_generator::iters::holistic;
+
+use crate::args::{self, Args, CliError, CliResult, Size};
+use crate::rules::{Rules, explore};
+
+pub const HELP: &str = "\
+Sample the serial and random pipelines, solvable or not, and report how many boards are solvable
+and how long their solutions are, as [min; average; max].
+
+Usage: sokoban stats [options]
+
+Options:
+    --size <n> | <w>x<h>   Only this board size [default: 4 to 7]
+    --seed <n>             Seed for the random pipelines [default: random]
+    --count <n>            Boards sampled from each pipeline, per size [default: 1000]
+    --pieces <n>           Number of pieces and goals per board [default: 1]
+    --push                 Classic push rules, with a player, instead of sliding
+    --output <file>        Write to a file instead of standard output";
+
+const MIN_SIZE: u8 = 4;
+const MAX_SIZE: u8 = 7;
+const NUM_ITERS: usize = 1000;
+
+type BoardIter = Box<dyn Iterator<Item = Board>>;
+
+pub fn run(mut args: Args) -> CliResult {
+    let sizes = match args.take_value::<Size>("--size")? {
+        Some(size) => vec![size],
+        None => (MIN_SIZE..=MAX_SIZE).map(|size| Size { width: size, height: size }).collect(),
+    };
+    let seed = args.take_value("--seed")?.unwrap_or_else(rand::random);
+    let num_iters = args.take_value("--count")?.unwrap_or(NUM_ITERS);
+    let num_pieces = args.take_value::<usize>("--pieces")?.unwrap_or(1);
+    let rules = Rules::from_flag(args.take_flag("--push"));
+    let output = args.take_value("--output")?;
+    args.finish_empty()?;
+    if num_pieces == 0 {
+        return Err(CliError::Usage("--pieces must be at least 1".to_string()));
+    }
+
+    let mut out = String::new();
+    out.push_str(&format!("Seed: {}. Iterations: {}.\n", seed, num_iters));
+
+    let general_timer = time::Instant::now();
+    for size in sizes {
+        let (width, height) = (size.width, size.height);
+        out.push_str(&format!("-------------------- SIZE: {:02}x{:02} [{:?}] --------------------\n", width, height, general_timer.elapsed()));
+
+        let phase_timer = time::Instant::now();
+
+        let mut iters: Vec<(&'static str, BoardIter, StatsCollector, StatsCollector)> = pipelines(width, height, num_pieces, seed, rules)
+            .into_iter()
+            .map(|(name, iter)| (name, iter, StatsCollector::new(), StatsCollector::new()))
+            .collect();
+
+        'iters: for _iteration in 0..num_iters {
+            for (_name, iter, stats_solved, stats_moves) in &mut iters {
+                if let Some(sample) = iter.next() {
+                    let explore_stats = explore(&sample, rules);
+                    stats_solved.observe(explore_stats.solvable as usize as f64);
+                    stats_moves.observe(explore_stats.num_moves as f64);
+                } else {
+                    break 'iters;
+                }
+            }
+        }
+
+        out.push_str(&format!("Duration: {:?}\n", phase_timer.elapsed()));
+
+        for (name, _iter, stats_solved, stats_moves) in &iters {
+            out.push_str(&format!("[{}] solved: {}; moves: {}\n", name, stats_solved, stats_moves));
+        }
+    }
+
+    return args::write_output(&out, &output);
+}
+
+/// The four pipelines compared, for the given rules.
+fn pipelines(width: u8, height: u8, num_pieces: usize, seed: u64, rules: Rules) -> Vec<(&'static str, BoardIter)> {
+    match rules {
+        Rules::Slide => vec![
+            ("    SERIAL     ", Box::new(holistic::serial_multi(width, height, num_pieces))),
+            ("    RANDOM     ", Box::new(holistic::random_multi(width, height, num_pieces, seed))),
+            ("SOLVABLE SERIAL", Box::new(holistic::solvable_serial_multi(width, height, num_pieces))),
+            ("SOLVABLE RANDOM", Box::new(holistic::solvable_random_multi(width, height, num_pieces, seed))),
+        ],
+        Rules::Push => vec![
+            ("    SERIAL     ", Box::new(holistic::serial_push(width, height, num_pieces))),
+            ("    RANDOM     ", Box::new(holistic::random_push(width, height, num_pieces, seed))),
+            ("SOLVABLE SERIAL", Box::new(holistic::solvable_serial_push(width, height, num_pieces))),
+            ("SOLVABLE RANDOM", Box::new(holistic::solvable_random_push(width, height, num_pieces, seed))),
+        ],
+    }
+}
+
+#[derive(Debug)]
+struct StatsCollector {
+    min: f64,
+    max: f64,
+    avg: f64,
+    n:   u32,
+}
+
+impl StatsCollector {
+    fn new() -> Self {
+        StatsCollector {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            avg: 0.0,
+            n: 0,
+        }
+    }
+
+    fn observe(&mut self, sample: f64) {
+        self.n += 1;
+        self.min = f64::min(self.min, sample);
+        self.max = f64::max(self.max, sample);
+        self.avg += (sample - self.avg) / (self.n as f64);
+    }
+}
+
+impl fmt::Display for StatsCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:.3}; {:.3}; {:.3}]", self.min, self.avg, self.max)
+    }
+}
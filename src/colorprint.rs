@@ -1,4 +1,3 @@
-use std::char;
 use crossterm::style::{Colorize, StyledContent};
 
 use crate::base::{Cell, Board};
@@ -9,17 +8,26 @@ pub fn color_cell_symbol(cell: &Cell) -> StyledContent<char> {
         Cell::Wall => 'X'.grey(),
         Cell::Piece => '#'.cyan(),
         Cell::Goal => '@'.magenta(),
-        Cell::Zone(num) => {
-            let symbol = char::from_digit(*num, 10).unwrap();
-            symbol.yellow()
-        },
+        Cell::PieceOnGoal => '*'.green(),
+        Cell::Player => 'P'.yellow(),
+        Cell::PlayerOnGoal => '+'.yellow(),
     }
 }
 
 pub fn color_print_board(board: &Board) {
-    for row in board {
-        for cell in row {
-            print!("{} ", color_cell_symbol(cell));
+    color_print_board_highlighted(board, &[]);
+}
+
+/// Like color_print_board(), with a background on the cells at the given (x,y) positions.
+pub fn color_print_board_highlighted(board: &Board, highlighted: &[(u8, u8)]) {
+    for (y, row) in board.rows().enumerate() {
+        for (x, cell) in row.enumerate() {
+            let symbol = color_cell_symbol(&cell);
+            if highlighted.contains(&(x as u8, y as u8)) {
+                print!("{} ", symbol.on_dark_blue());
+            } else {
+                print!("{} ", symbol);
+            }
         }
         print!("\r\n");
     }
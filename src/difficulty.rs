@@ -0,0 +1,133 @@
+//! Difficulty rating for boards under the sliding rules (see play).
+//!
+//! Each rating combines a handful of metrics taken from the full state space (see graph::StateGraph)
+//! into a single score. Longer solutions, bigger state spaces, more traps and more decoys make a board
+//! harder; having several optimal solutions makes it easier.
+
+use crate::base::Board;
+use crate::graph::StateGraph;
+use crate::play::{count_optimal, step, DIRECTIONS};
+
+/// Metrics describing how hard a board is, and the resulting score.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    /// Length of the shortest solution.
+    pub num_moves: usize,
+    /// Number of reachable piece positions, including the initial one.
+    pub num_states: usize,
+    /// Fraction of reachable states from which the board can't be solved anymore.
+    pub trap_fraction: f64,
+    /// Number of distinct shortest solutions.
+    pub num_optimal: u64,
+    /// Number of trap states with a piece right next to an uncovered goal.
+    pub num_decoys: usize,
+    /// Overall difficulty. See score().
+    pub score: f64,
+}
+
+/// Rate a board. Returns None if it can't be solved.
+pub fn rate(board: &Board) -> Option<Difficulty> {
+    let graph = StateGraph::new(board);
+    let num_moves = graph.distance_to_goal(0)?;
+    let dead_ends = graph.dead_ends();
+    let num_decoys = dead_ends.iter().filter(|idx| is_decoy(&graph.board_at(**idx))).count();
+
+    let mut difficulty = Difficulty {
+        num_moves: num_moves,
+        num_states: graph.num_states(),
+        trap_fraction: dead_ends.len() as f64 / graph.num_states() as f64,
+        num_optimal: count_optimal(board).num_solutions,
+        num_decoys: num_decoys,
+        score: 0.0,
+    };
+    difficulty.score = score(&difficulty);
+
+    return Some(difficulty);
+}
+
+/// Combine the metrics into a single non-negative number:
+///
+/// `num_moves * (1 + trap_fraction) + log2(num_states) + log2(1 + num_decoys) - log2(num_optimal)`
+pub fn score(difficulty: &Difficulty) -> f64 {
+    let score = difficulty.num_moves as f64 * (1.0 + difficulty.trap_fraction)
+        + (difficulty.num_states as f64).log2()
+        + (1.0 + difficulty.num_decoys as f64).log2()
+        - (difficulty.num_optimal.max(1) as f64).log2();
+
+    return score.max(0.0);
+}
+
+/// True if some piece lies next to a goal that is still uncovered.
+fn is_decoy(board: &Board) -> bool {
+    let goals = board.goal_set();
+    let pieces = board.piece_set();
+
+    return pieces.iter().any(|idx| {
+        DIRECTIONS.iter().any(|dir| match step(board, board.position(idx), *dir) {
+            Some((x, y)) => {
+                let next_idx = board.cell_index(x as usize, y as usize);
+                goals.contains(next_idx) && !pieces.contains(next_idx)
+            },
+            None => false,
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(trap_fraction: f64, num_decoys: usize) -> Difficulty {
+        let mut difficulty = Difficulty {
+            num_moves: 4,
+            num_states: 8,
+            trap_fraction: trap_fraction,
+            num_optimal: 2,
+            num_decoys: num_decoys,
+            score: 0.0,
+        };
+        difficulty.score = score(&difficulty);
+        return difficulty;
+    }
+
+    #[test]
+    fn formula() {
+        // 4 * 1.5 + log2(8) + log2(4) - log2(2)
+        assert_eq!(metrics(0.5, 3).score, 10.0);
+        assert_eq!(metrics(0.0, 0).score, 6.0);
+        assert!(metrics(0.25, 0).score > 6.0);
+        assert!(metrics(0.0, 1).score > 6.0);
+
+        let easy = Difficulty { num_moves: 0, num_states: 1, num_optimal: 4, ..metrics(0.0, 0) };
+        assert_eq!(score(&easy), 0.0);
+    }
+
+    #[test]
+    fn traps_and_decoys_score_higher() {
+        // No traps: the piece can always come back.
+        let open = rate(&"#.\n.@\n".parse().unwrap()).unwrap();
+        assert_eq!((open.num_moves, open.num_states, open.trap_fraction, open.num_optimal), (2, 4, 0.0, 2));
+        assert_eq!(open.score, 3.0);
+
+        // Sliding sideways traps the piece in a corner, even though the solution is shorter.
+        let trap = rate(&".#.\nX@X\n".parse().unwrap()).unwrap();
+        assert_eq!((trap.num_moves, trap.trap_fraction, trap.num_decoys), (1, 0.5, 0));
+        assert!(trap.score > open.score);
+
+        // Some of the traps leave a piece right next to an empty goal.
+        let decoy = rate(&"X#@\n@.X\n#XX\n".parse().unwrap()).unwrap();
+        assert_eq!((decoy.num_moves, decoy.num_states, decoy.num_decoys), (2, 8, 3));
+        assert_eq!(decoy.score, 2.0 * 1.625 + 3.0 + 2.0);
+        assert!(decoy.score > open.score);
+
+        assert_eq!(rate(&"#X@\n".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn decoys() {
+        assert!(is_decoy(&"#@\n..\n".parse().unwrap()));
+        assert!(!is_decoy(&"#.\n.@\n".parse().unwrap()));
+        // The goal next to the piece is already covered.
+        assert!(!is_decoy(&"#*\nX.\n".parse().unwrap()));
+    }
+}
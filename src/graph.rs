@@ -0,0 +1,283 @@
+//! Full state space of a board under the sliding rules (see play).
+//!
+//! Where explore_space() stops at the first solution, StateGraph keeps every reachable piece position
+//! and every move between them, for level quality metrics.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::base::{Board, CellSet};
+use crate::play::{Direction, DIRECTIONS, tilt};
+
+/// Directed graph of piece positions. Edges are moves that change the board.
+///
+/// Solved states have no outgoing edges: the game ends as soon as every goal is covered.
+pub struct StateGraph {
+    /// Board the graph was built from. Its walls and goals are shared by every state.
+    board: Board,
+    /// Every reachable piece position, in breadth-first order. The initial one comes first.
+    states: Vec<CellSet>,
+    /// Position of each state in states, for constant time lookup.
+    index: HashMap<CellSet, usize>,
+    /// Outgoing moves of each state, with the index of the resulting state.
+    edges: Vec<Vec<(Direction, usize)>>,
+    /// Moves needed to reach a solved state from each state. None for dead ends.
+    distances: Vec<Option<usize>>,
+}
+
+impl StateGraph {
+    /// Explore every position reachable from the board.
+    pub fn new(board: &Board) -> StateGraph {
+        let init_pieces = board.piece_set();
+        let mut graph = StateGraph {
+            board: board.clone(),
+            states: vec![init_pieces],
+            index: HashMap::new(),
+            edges: Vec::new(),
+            distances: Vec::new(),
+        };
+        graph.index.insert(init_pieces, 0);
+
+        let mut idx = 0;
+        while idx < graph.states.len() {
+            let pieces = graph.states[idx];
+            let mut edges = Vec::new();
+
+            if !graph.is_solved(idx) {
+                for dir in &DIRECTIONS {
+                    let (new_pieces, cells_moved) = tilt(board, pieces, *dir);
+                    if cells_moved == 0 {
+                        continue;
+                    }
+
+                    let new_idx = match graph.index.get(&new_pieces) {
+                        Some(&new_idx) => new_idx,
+                        None => {
+                            graph.index.insert(new_pieces, graph.states.len());
+                            graph.states.push(new_pieces);
+                            graph.states.len() - 1
+                        },
+                    };
+                    edges.push((*dir, new_idx));
+                }
+            }
+
+            graph.edges.push(edges);
+            idx += 1;
+        }
+
+        graph.distances = graph.compute_distances();
+        return graph;
+    }
+
+    /// Breadth-first search from the solved states, following edges backwards.
+    fn compute_distances(&self) -> Vec<Option<usize>> {
+        let mut reverse: Vec<Vec<usize>> = vec![Vec::new(); self.states.len()];
+        for (idx, edges) in self.edges.iter().enumerate() {
+            for (_, next) in edges {
+                reverse[*next].push(idx);
+            }
+        }
+
+        let mut distances = vec![None; self.states.len()];
+        let mut pending = VecDeque::new();
+        for idx in self.solved_states() {
+            distances[idx] = Some(0);
+            pending.push_back(idx);
+        }
+
+        while let Some(idx) = pending.pop_front() {
+            let distance = distances[idx].unwrap() + 1;
+            for prev in &reverse[idx] {
+                if distances[*prev].is_none() {
+                    distances[*prev] = Some(distance);
+                    pending.push_back(*prev);
+                }
+            }
+        }
+
+        return distances;
+    }
+
+    /// Board the graph was built from.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Number of reachable states, including the initial one.
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Total number of moves between states.
+    pub fn num_edges(&self) -> usize {
+        self.edges.iter().map(|edges| edges.len()).sum()
+    }
+
+    /// Piece positions of every state, by index. The initial state is index 0.
+    pub fn states(&self) -> &[CellSet] {
+        &self.states
+    }
+
+    /// Index of the state with the given piece positions, if reachable.
+    pub fn index_of(&self, pieces: &CellSet) -> Option<usize> {
+        self.index.get(pieces).cloned()
+    }
+
+    /// The original board with the pieces of state idx.
+    pub fn board_at(&self, idx: usize) -> Board {
+        let mut board = self.board.clone();
+        board.set_pieces(self.states[idx]);
+        return board;
+    }
+
+    /// Moves out of state idx, with the index of the resulting state.
+    pub fn edges(&self, idx: usize) -> &[(Direction, usize)] {
+        &self.edges[idx]
+    }
+
+    /// True if every goal is covered in state idx. Boards without goals are never solved.
+    pub fn is_solved(&self, idx: usize) -> bool {
+        let goals = self.board.goal_set();
+        return !goals.is_empty() && self.states[idx].is_superset(&goals);
+    }
+
+    /// Indices of every solved state.
+    pub fn solved_states(&self) -> Vec<usize> {
+        (0..self.states.len()).filter(|idx| self.is_solved(*idx)).collect()
+    }
+
+    /// Fewest moves from state idx to a solved state, or None if it's a dead end.
+    pub fn distance_to_goal(&self, idx: usize) -> Option<usize> {
+        self.distances[idx]
+    }
+
+    /// Indices of the states that can't reach a solved state anymore.
+    pub fn dead_ends(&self) -> Vec<usize> {
+        (0..self.states.len()).filter(|idx| self.distances[*idx].is_none()).collect()
+    }
+
+    /// Average number of moves out of each unsolved state. Zero if there are none.
+    pub fn branching_factor(&self) -> f64 {
+        let unsolved: Vec<usize> = (0..self.states.len()).filter(|idx| !self.is_solved(*idx)).collect();
+        if unsolved.is_empty() {
+            return 0.0;
+        }
+
+        let num_edges: usize = unsolved.iter().map(|idx| self.edges[*idx].len()).sum();
+        return num_edges as f64 / unsolved.len() as f64;
+    }
+
+    /// Strongly connected components (Tarjan's algorithm), as lists of state indices.
+    ///
+    /// States in a component can all reach each other. Components come out in reverse topological
+    /// order: no edge leads from a component to one listed after it.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let num_states = self.states.len();
+        let mut order = vec![usize::MAX; num_states];
+        let mut lowlink = vec![0; num_states];
+        let mut on_stack = vec![false; num_states];
+        let mut stack: Vec<usize> = Vec::new();
+        let mut counter = 0;
+        let mut out = Vec::new();
+
+        for root in 0..num_states {
+            if order[root] != usize::MAX {
+                continue;
+            }
+
+            // Explicit call stack of (state, next edge to follow), to avoid deep recursion.
+            let mut calls = vec![(root, 0)];
+            order[root] = counter;
+            lowlink[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((idx, edge)) = calls.pop() {
+                if edge < self.edges[idx].len() {
+                    calls.push((idx, edge + 1));
+                    let next = self.edges[idx][edge].1;
+                    if order[next] == usize::MAX {
+                        order[next] = counter;
+                        lowlink[next] = counter;
+                        counter += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        calls.push((next, 0));
+                    } else if on_stack[next] {
+                        lowlink[idx] = lowlink[idx].min(order[next]);
+                    }
+                    continue;
+                }
+
+                if lowlink[idx] == order[idx] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == idx {
+                            break;
+                        }
+                    }
+                    out.push(component);
+                }
+
+                if let Some(&(parent, _)) = calls.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[idx]);
+                }
+            }
+        }
+
+        return out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iters::holistic;
+    use crate::play::explore_space;
+
+    #[test]
+    fn trap() {
+        // Down solves it. Left or right sends the piece to a corner it can only slide between.
+        let board: Board = ".#.\nX@X\n".parse().unwrap();
+        let graph = StateGraph::new(&board);
+        assert_eq!(graph.num_states(), 4);
+        assert_eq!(graph.num_edges(), 5);
+        assert_eq!(graph.distance_to_goal(0), Some(explore_space(&board).num_moves));
+        assert_eq!(graph.solved_states(), vec![1]);
+        assert_eq!(graph.dead_ends(), vec![2, 3]);
+        assert_eq!(graph.branching_factor(), 5.0 / 3.0);
+
+        let mut components = graph.components();
+        components.iter_mut().for_each(|component| component.sort());
+        assert_eq!(components, vec![vec![1], vec![2, 3], vec![0]]);
+    }
+
+    #[test]
+    fn random_boards() {
+        let boards = holistic::random_multi(5, 5, 2, 5).take(200);
+        for board in boards {
+            let graph = StateGraph::new(&board);
+            let stats = explore_space(&board);
+            let expected = if stats.solvable { Some(stats.num_moves) } else { None };
+            assert_eq!(graph.distance_to_goal(0), expected, "{}", board);
+
+            // Every state is in exactly one component, after every component it has an edge to.
+            let components = graph.components();
+            let mut position = vec![None; graph.num_states()];
+            for (idx, component) in components.iter().enumerate() {
+                for state in component {
+                    assert_eq!(position[*state], None, "{}", board);
+                    position[*state] = Some(idx);
+                }
+            }
+            for idx in 0..graph.num_states() {
+                for (_, next) in graph.edges(idx) {
+                    assert!(position[*next] <= position[idx], "{}", board);
+                }
+            }
+        }
+    }
+}
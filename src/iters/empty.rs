@@ -1,9 +1,8 @@
 //! This module is dedicated to the creation of valid empty boards. See empty::Maker.
 
-use std::u128;
 use rand;
-use rand::Rng;
-// use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
 
 use crate::base::{Board, Cell, BaseIter};
 use crate::iters::filter::Symmetries;
@@ -25,34 +24,183 @@ pub fn random(size: u8) -> Symmetries<RngMaker> {
     Symmetries::<RngMaker>::new_base(size)
 }
 
+/// Exhaustive empty board maker for width x height boards. See serial().
+pub fn serial_rect(width: u8, height: u8) -> Symmetries<SerialMaker> {
+    Symmetries::<SerialMaker>::new_rect(width, height)
+}
+
+/// RNG-based empty board maker for width x height boards. See random().
+pub fn random_rect(width: u8, height: u8) -> Symmetries<RngMaker> {
+    Symmetries::<RngMaker>::new_rect(width, height)
+}
+
+/// RNG-based empty board maker with a fixed seed. The same seed always yields the same boards.
+pub fn random_seeded(width: u8, height: u8, seed: u64) -> Symmetries<RngMaker> {
+    Symmetries::<RngMaker>::new_seeded(width, height, seed)
+}
+
 /// Makes all  unique empty boards of the given size.
+///
+/// Walks wall layouts with a backtracking search, assigning cells from the last one (bottom right)
+/// to the first one (top left), wall before floor. Boards come out in the same order as counting
+/// through every bit pattern, but branches that cannot lead to a valid board (see valid_board())
+/// are cut as soon as they are detected, so any board size can be enumerated.
 pub struct SerialMaker {
-    /// Next value used to determine the pattern of floor and wall tiles. Range [0, self.combinations).
-    seed: u128,
-    /// Number of rows (or columns) in each board.
-    size: u8,
-    /// Total number of floor & wall combinations possible in this board size (2 ^ (size * size)).
-    combinations: u128,
+    /// Number of columns in each board.
+    width: u8,
+    /// Number of rows in each board.
+    height: u8,
+    /// Cells assigned so far, true for floor. choices[i] holds cell width * height - 1 - i.
+    choices: Vec<bool>,
+    /// True once every branch has been explored.
+    done: bool,
 }
 
 impl BaseIter for SerialMaker {
-    /// Returns a new Maker for the given board size.
-    fn new_base(size: u8) -> SerialMaker {
+    /// Returns a new Maker for the given board dimensions.
+    fn new_rect(width: u8, height: u8) -> SerialMaker {
         return SerialMaker {
-            seed: 0,
-            size: size,
-            combinations: 1 << (size * size),
+            width: width,
+            height: height,
+            choices: Vec::new(),
+            done: false,
         };
     }
 }
 
+impl SerialMaker {
+    fn num_cells(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// Value of cell idx (row-major), if already assigned.
+    fn cell(&self, idx: usize) -> Option<bool> {
+        let pos = self.num_cells() - 1 - idx;
+        self.choices.get(pos).cloned()
+    }
+
+    /// Move to the next unexplored sibling: drop trailing floors, then turn the last wall into floor.
+    /// Returns false once the whole tree has been explored.
+    fn backtrack(&mut self) -> bool {
+        while let Some(true) = self.choices.last() {
+            self.choices.pop();
+        }
+
+        match self.choices.last_mut() {
+            Some(last) => {
+                *last = true;
+                return true;
+            },
+            None => return false,
+        }
+    }
+
+    /// Returns false if no assignment of the remaining cells can make a valid board.
+    fn feasible(&self) -> bool {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let num_cells = self.num_cells();
+        // Cells [0, unassigned) are still free.
+        let unassigned = num_cells - self.choices.len();
+
+        // Bounding box: both floor and walls must reach x <= 1, y <= 1, x >= width - 1, y >= height - 1.
+        // The first two can be met by cell 0 as long as it is free.
+        let can_reach_right = unassigned >= width;
+        let can_reach_bottom = unassigned > (height - 1) * width;
+        for value in &[true, false] {
+            let mut left = unassigned > 0;
+            let mut top = unassigned > 0;
+            let mut right = can_reach_right;
+            let mut bottom = can_reach_bottom;
+            for idx in unassigned..num_cells {
+                if self.cell(idx) == Some(*value) {
+                    let (x, y) = (idx % width, idx / width);
+                    left |= x <= 1;
+                    top |= y <= 1;
+                    right |= x + 1 >= width;
+                    bottom |= y + 1 >= height;
+                }
+            }
+            if !(left && top && right && bottom) {
+                return false;
+            }
+        }
+
+        // Connectivity: a floor component with no free neighbors can't grow, so it must be the only one.
+        let mut component = vec![usize::MAX; num_cells];
+        let mut num_components = 0;
+        let mut closed = false;
+        for start in unassigned..num_cells {
+            if self.cell(start) != Some(true) || component[start] != usize::MAX {
+                continue;
+            }
+
+            let mut open = false;
+            let mut pending = vec![start];
+            component[start] = num_components;
+            while let Some(idx) = pending.pop() {
+                let (x, y) = (idx % width, idx / width);
+                let mut neighbors = Vec::with_capacity(4);
+                if x > 0 { neighbors.push(idx - 1); }
+                if x + 1 < width { neighbors.push(idx + 1); }
+                if y > 0 { neighbors.push(idx - width); }
+                if y + 1 < height { neighbors.push(idx + width); }
+
+                for next in neighbors {
+                    match self.cell(next) {
+                        None => open = true,
+                        Some(true) if component[next] == usize::MAX => {
+                            component[next] = num_components;
+                            pending.push(next);
+                        },
+                        _ => (),
+                    }
+                }
+            }
+
+            num_components += 1;
+            closed |= !open;
+            if closed && num_components > 1 {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    fn make_board(&self) -> Board {
+        let mut board = Board::new(self.width, self.height);
+        for idx in 0..self.num_cells() {
+            if self.cell(idx) == Some(true) {
+                board.set(idx % self.width as usize, idx / self.width as usize, Cell::Floor);
+            }
+        }
+
+        return board;
+    }
+}
+
 impl Iterator for SerialMaker {
     type Item = Board;
     
     fn next(&mut self) -> Option<Board> {
-        while self.seed < self.combinations {
-            let board = make_board(self.size, self.seed);
-            self.seed += 1;
+        if self.width < 2 || self.height < 2 {
+            self.done = true;
+        }
+
+        while !self.done {
+            if !self.feasible() {
+                self.done = !self.backtrack();
+                continue;
+            }
+
+            if self.choices.len() < self.num_cells() {
+                self.choices.push(false);
+                continue;
+            }
+
+            let board = self.make_board();
+            self.done = !self.backtrack();
 
             if valid_board(&board) {
                 return Some(board);
@@ -65,9 +213,9 @@ impl Iterator for SerialMaker {
 
 /// Calculates various stats for the given board and ensures they're within the desired ranges.
 fn valid_board(board: &Board) -> bool {
-    let size = board.len();
+    let (width, height) = board.dimensions();
 
-    if size < 2 { return false; }
+    if width < 2 || height < 2 { return false; }
 
     if count_connected_components(board) != 1 {
         return false;
@@ -80,17 +228,18 @@ fn valid_board(board: &Board) -> bool {
         return false;
     }
 
-    if !check_limits(floor_stats, 1, size - 1) || !check_limits(wall_stats, 1, size - 1) {
+    let max = (width - 1, height - 1);
+    if !check_limits(floor_stats, (1, 1), max) || !check_limits(wall_stats, (1, 1), max) {
         return false;
     }
 
     return true;
 }
 
-/// Returns true if stats has valid bounds and they contain the square delimited by x, y ∈ [min, max].
-fn check_limits(stats: stats::CellStats, min: usize, max: usize) -> bool {
+/// Returns true if stats has valid bounds and they contain the rectangle delimited by min and max corners.
+fn check_limits(stats: stats::CellStats, min: (usize, usize), max: (usize, usize)) -> bool {
     if let (Some(stats_min), Some(stats_max)) = (stats.min, stats.max) {
-        if stats_min.0 <= min && stats_min.1 <= min && stats_max.0 >= max && stats_max.1 >= max {
+        if stats_min.0 <= min.0 && stats_min.1 <= min.1 && stats_max.0 >= max.0 && stats_max.1 >= max.1 {
             return true;
         } 
     }
@@ -100,47 +249,34 @@ fn check_limits(stats: stats::CellStats, min: usize, max: usize) -> bool {
 
 /// Returns the number of connected components.
 fn count_connected_components(board: &Board) -> usize {
-    let mut dummy = board.clone();
-    let counts = fill::mark_components(&mut dummy);
+    let (_zones, counts) = fill::mark_components(board);
     return counts.len();
 }
 
-fn make_board(size: u8, seed: u128) -> Board {
-    let mut board = Vec::new();
-    for y in 0..size {
-        let mut row = Vec::new();
-        for x in 0..size {
-            let index = y * size + x;
-            let mask = (seed >> index) & 1;
-            let cell = if mask == 1 { Cell::Floor } else { Cell::Wall };
-            row.push(cell);
-        }
-        board.push(row);
-    }
-
-    return board;
-}
-
 pub struct RngMaker {
-    // Number of rows (or columns) in each board.
-    size: u8,
+    /// Number of columns in each board.
+    width: u8,
+    /// Number of rows in each board.
+    height: u8,
     /// This is the magic sauce.
-    rng: rand::rngs::ThreadRng,
+    rng: Pcg64,
     /// Safety measure to avoid infinite looping.
     /// Looks like we're skipping one combination?
     count: u128,
-    /// Total number of floor & wall combinations possible in this board size (2 ^ (size * size)).
-    /// DUPLICATE IN SerialMaker.
-    combinations: u128,
 }
 
 impl BaseIter for RngMaker {
-    fn new_base(size: u8) -> RngMaker {
+    /// Returns a new Maker with a random seed.
+    fn new_rect(width: u8, height: u8) -> RngMaker {
+        return RngMaker::new_seeded(width, height, rand::random());
+    }
+
+    fn new_seeded(width: u8, height: u8, seed: u64) -> RngMaker {
         return RngMaker {
-            size: size,
-            rng: rand::thread_rng(),
+            width: width,
+            height: height,
+            rng: Pcg64::seed_from_u64(seed),
             count: 0,
-            combinations: 1 << (size * size),
         }
     }
 }
@@ -148,13 +284,17 @@ impl BaseIter for RngMaker {
 impl Iterator for RngMaker {
     type Item = Board;
 
-    // TODO: LOTS OF OVERLAP WITH SerialMaker.
     fn next(&mut self) -> Option<Self::Item> {
         while self.count < u128::MAX { // DANGEROUS
             self.count += 1;
-            let seed: u128 = self.rng.gen::<u128>() % self.combinations;
-            // println!("seed: {} / {}", seed, self.combinations);
-            let board = make_board(self.size, seed);
+            let mut board = Board::new(self.width, self.height);
+            for y in 0..self.height as usize {
+                for x in 0..self.width as usize {
+                    if self.rng.gen::<bool>() {
+                        board.set(x, y, Cell::Floor);
+                    }
+                }
+            }
 
             if valid_board(&board) {
                 return Some(board);
@@ -1,8 +1,15 @@
+use std::marker::PhantomData;
+
 use rand;
+use rand::SeedableRng;
 use rand::seq::{SliceRandom, IteratorRandom};
+use rand_pcg::Pcg64;
 
 use crate::base::{Board, Cell, SecondaryIter};
 use crate::iters::filter::Symmetries;
+use crate::play::explore_space;
+use crate::retrograde;
+use crate::rules::{RuleSet, Slide, Push};
 
 /// Provides an exhaustive board filler.
 pub fn serial(board: Board) -> Symmetries<SerialFiller> {
@@ -14,21 +21,85 @@ pub fn random(board: Board) -> Symmetries<RngFiller> {
     Symmetries::<RngFiller>::new_secondary(board)
 }
 
+/// Provides a PRNG board filler with a fixed seed. The same seed always yields the same boards.
+pub fn random_seeded(board: Board, seed: u64) -> Symmetries<RngFiller> {
+    Symmetries::<RngFiller>::new_secondary_seeded(board, seed)
+}
+
+/// Provides an exhaustive board filler placing the given number of pieces and goals.
+pub fn serial_multi(board: Board, num_pieces: usize) -> Symmetries<SerialFiller> {
+    Symmetries::<SerialFiller>::new_secondary_multi(board, num_pieces, 0)
+}
+
+/// Provides a PRNG board filler placing the given number of pieces and goals, with a fixed seed.
+pub fn random_multi(board: Board, num_pieces: usize, seed: u64) -> Symmetries<RngFiller> {
+    Symmetries::<RngFiller>::new_secondary_multi(board, num_pieces, seed)
+}
+
+/// Provides an exhaustive board filler for push rules: pieces, goals and a player.
+pub fn serial_push(board: Board, num_pieces: usize) -> Symmetries<SerialPushFiller> {
+    Symmetries::<SerialPushFiller>::new_secondary_multi(board, num_pieces, 0)
+}
+
+/// Provides a PRNG board filler for push rules: pieces, goals and a player, with a fixed seed.
+pub fn random_push(board: Board, num_pieces: usize, seed: u64) -> Symmetries<RngPushFiller> {
+    Symmetries::<RngPushFiller>::new_secondary_multi(board, num_pieces, seed)
+}
+
+/// Provides a board filler placing, for every layout of the pieces, the goals that make the longest puzzle.
+pub fn longest(board: Board, num_pieces: usize) -> Symmetries<LongestFiller> {
+    Symmetries::<LongestFiller>::new_secondary_multi(board, num_pieces, 0)
+}
+
+/// Provides a board filler placing goals on every combination of floor cells, with the pieces as far
+/// from a solution as possible.
+pub fn hardest(board: Board, num_pieces: usize) -> Symmetries<RetrogradeFiller> {
+    Symmetries::<RetrogradeFiller>::new_secondary_multi(board, num_pieces, 0)
+}
+
+/// Places pieces and goals on every possible combination of floor cells. Pieces never start on goals.
 pub struct SerialFiller {
     empty_board: Board,
     slots: Vec<(usize, usize)>,
-    box_idx: usize,
-    goal_idx: usize,
+    /// Indices into slots for the pieces, in increasing order.
+    box_idxs: Vec<usize>,
+    /// Slots left free by the current pieces.
+    free_slots: Vec<(usize, usize)>,
+    /// Indices into free_slots for the goals, in increasing order.
+    goal_idxs: Vec<usize>,
+    done: bool,
 }
 
 impl SecondaryIter for SerialFiller {
     fn new_secondary(empty_board: Board) -> SerialFiller {
-        return SerialFiller {
-            slots: get_slots(&empty_board),
+        return SerialFiller::new_secondary_multi(empty_board, 1, 0);
+    }
+
+    /// The seed is unused: the order is always the same.
+    fn new_secondary_multi(empty_board: Board, num_pieces: usize, _seed: u64) -> SerialFiller {
+        let slots = get_slots(&empty_board);
+        let box_idxs: Vec<usize> = (0..num_pieces).collect();
+        let mut filler = SerialFiller {
+            done: num_pieces == 0 || slots.len() < 2 * num_pieces,
             empty_board: empty_board,
-            box_idx: 0,
-            goal_idx: 1,
+            slots: slots,
+            free_slots: Vec::new(),
+            goal_idxs: box_idxs.clone(),
+            box_idxs: box_idxs,
         };
+        filler.update_free_slots();
+
+        return filler;
+    }
+}
+
+impl SerialFiller {
+    fn update_free_slots(&mut self) {
+        let box_idxs = &self.box_idxs;
+        self.free_slots = self.slots.iter().enumerate()
+            .filter(|(idx, _)| !box_idxs.contains(idx))
+            .map(|(_, slot)| *slot)
+            .collect();
     }
 }
 
@@ -36,27 +107,31 @@ impl Iterator for SerialFiller {
     type Item = Board;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.box_idx >= self.slots.len() {
+        if self.done {
             return None;
         }
 
         // Clone empty board and add Cell::Piece, Cell::Goal.
         let mut filled_board = self.empty_board.clone();
 
-        let (box_i, box_j) = self.slots[self.box_idx];
-        filled_board[box_j][box_i] = Cell::Piece;
+        for idx in &self.box_idxs {
+            let (box_i, box_j) = self.slots[*idx];
+            filled_board.set(box_i, box_j, Cell::Piece);
+        }
 
-        let (goal_i, goal_j) = self.slots[self.goal_idx];
-        filled_board[goal_j][goal_i] = Cell::Goal;
+        for idx in &self.goal_idxs {
+            let (goal_i, goal_j) = self.free_slots[*idx];
+            filled_board.set(goal_i, goal_j, Cell::Goal);
+        }
 
         // Update indices.
-        self.goal_idx += 1;
-        if self.goal_idx == self.box_idx {
-            self.goal_idx += 1;
-        }
-        if self.goal_idx >= self.slots.len() {
-            self.box_idx += 1;
-            self.goal_idx = 0;
+        if !next_combination(&mut self.goal_idxs, self.free_slots.len()) {
+            if next_combination(&mut self.box_idxs, self.slots.len()) {
+                self.goal_idxs = (0..self.box_idxs.len()).collect();
+                self.update_free_slots();
+            } else {
+                self.done = true;
+            }
         }
 
         // If all is good, return filled board.
@@ -64,52 +139,314 @@ impl Iterator for SerialFiller {
     }
 }
 
-pub struct RngFiller {
+/// Advance an increasing sequence of indices in [0, n) to the next one in lexicographic order.
+/// Returns false if it was the last one.
+fn next_combination(idxs: &mut [usize], n: usize) -> bool {
+    let k = idxs.len();
+    for i in (0..k).rev() {
+        if idxs[i] < n - k + i {
+            idxs[i] += 1;
+            for j in i + 1..k {
+                idxs[j] = idxs[j - 1] + 1;
+            }
+            return true;
+        }
+    }
+
+    return false;
+}
+
+/// Random piece and goal layouts for an empty board, plus a player on one more free floor cell
+/// under rules that have one.
+///
+/// Some empty boards have no solvable layout at all, so this gives up after MAX_DRAWS boards.
+pub struct RngFiller<R: RuleSet = Slide> {
     empty_board: Board,
     slots: Vec<(usize, usize)>,
-    rng: rand::rngs::ThreadRng,
+    num_pieces: usize,
+    rng: Pcg64,
+    /// Number of boards returned so far.
+    count: usize,
+    rules: PhantomData<R>,
 }
 
-impl SecondaryIter for RngFiller {
-    fn new_secondary(empty_board: Board) -> RngFiller {
+/// Like RngFiller, with a player for push rules (see crate::push).
+pub type RngPushFiller = RngFiller<Push>;
+
+/// Number of layouts RngFiller tries on a single empty board.
+pub const MAX_DRAWS: usize = 1000;
+
+impl<R: RuleSet> SecondaryIter for RngFiller<R> {
+    /// Returns a new Filler with a random seed.
+    fn new_secondary(empty_board: Board) -> Self {
+        return Self::new_secondary_seeded(empty_board, rand::random());
+    }
+
+    fn new_secondary_seeded(empty_board: Board, seed: u64) -> Self {
+        return Self::new_secondary_multi(empty_board, 1, seed);
+    }
+
+    fn new_secondary_multi(empty_board: Board, num_pieces: usize, seed: u64) -> Self {
         return RngFiller {
             slots: get_slots(&empty_board),
             empty_board: empty_board,
-            rng: rand::thread_rng(),
-
+            num_pieces: num_pieces,
+            rng: Pcg64::seed_from_u64(seed),
+            count: 0,
+            rules: PhantomData,
         };
     }
 }
 
-impl Iterator for RngFiller {
+impl<R: RuleSet> Iterator for RngFiller<R> {
     type Item = Board;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let num_slots = 2 * self.num_pieces + R::NUM_PLAYERS;
+        if self.num_pieces == 0 || self.slots.len() < num_slots || self.count >= MAX_DRAWS {
+            return None;
+        }
+        self.count += 1;
+
         let mut filled_board = self.empty_board.clone();
-        let mut slots: Vec<(usize, usize)> = self.slots.iter().cloned().choose_multiple(&mut self.rng, 2);
+        let mut slots: Vec<(usize, usize)> = self.slots.iter().cloned().choose_multiple(&mut self.rng, num_slots);
         slots.shuffle(&mut self.rng);
         
-        let (box_i, box_j) = slots[0];
-        filled_board[box_j][box_i] = Cell::Piece;
+        for (box_i, box_j) in &slots[..self.num_pieces] {
+            filled_board.set(*box_i, *box_j, Cell::Piece);
+        }
 
-        let (goal_i, goal_j) = slots[1];
-        filled_board[goal_j][goal_i] = Cell::Goal;
+        for (goal_i, goal_j) in &slots[self.num_pieces..2 * self.num_pieces] {
+            filled_board.set(*goal_i, *goal_j, Cell::Goal);
+        }
+
+        for (player_i, player_j) in &slots[2 * self.num_pieces..] {
+            filled_board.set(*player_i, *player_j, Cell::Player);
+        }
 
         return Some(filled_board);
     }
 }
 
+/// Goes through every piece layout in SerialFiller order. For each one, returns the goal placement
+/// with the longest shortest solution (the first one found on ties). Layouts that can't be solved
+/// whatever the goals are skipped.
+pub struct LongestFiller {
+    filler: SerialFiller,
+    /// Next board from filler, if already pulled.
+    pending: Option<Board>,
+}
+
+impl SecondaryIter for LongestFiller {
+    fn new_secondary(empty_board: Board) -> LongestFiller {
+        return LongestFiller::new_secondary_multi(empty_board, 1, 0);
+    }
+
+    /// The seed is unused: the order is always the same.
+    fn new_secondary_multi(empty_board: Board, num_pieces: usize, seed: u64) -> LongestFiller {
+        return LongestFiller {
+            filler: SerialFiller::new_secondary_multi(empty_board, num_pieces, seed),
+            pending: None,
+        };
+    }
+}
+
+impl Iterator for LongestFiller {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let first = self.pending.take().or_else(|| self.filler.next())?;
+            let pieces = first.piece_set();
+            let mut best: Option<(usize, Board)> = None;
+
+            // SerialFiller goes through all goal placements for a piece layout before moving on.
+            let mut candidate = Some(first);
+            while let Some(board) = candidate {
+                if board.piece_set() != pieces {
+                    self.pending = Some(board);
+                    break;
+                }
+
+                let stats = explore_space(&board);
+                if stats.solvable && best.as_ref().map_or(true, |(num_moves, _)| stats.num_moves > *num_moves) {
+                    best = Some((stats.num_moves, board));
+                }
+                candidate = self.filler.next();
+            }
+
+            if let Some((_, board)) = best {
+                return Some(board);
+            }
+        }
+    }
+}
+
+/// Places goals on every combination of floor cells, in increasing order. For each one, searches
+/// backwards from the goals (see retrograde::hardest()) and returns every board with the pieces at
+/// the largest distance. Goal placements that no other position can reach are skipped.
+pub struct RetrogradeFiller {
+    empty_board: Board,
+    slots: Vec<(usize, usize)>,
+    /// Indices into slots for the goals, in increasing order.
+    goal_idxs: Vec<usize>,
+    /// Boards found for the current goals, not returned yet.
+    pending: Vec<Board>,
+    done: bool,
+}
+
+impl SecondaryIter for RetrogradeFiller {
+    fn new_secondary(empty_board: Board) -> RetrogradeFiller {
+        return RetrogradeFiller::new_secondary_multi(empty_board, 1, 0);
+    }
+
+    /// The seed is unused: the order is always the same.
+    fn new_secondary_multi(empty_board: Board, num_pieces: usize, _seed: u64) -> RetrogradeFiller {
+        let slots = get_slots(&empty_board);
+        return RetrogradeFiller {
+            done: num_pieces == 0 || slots.len() < 2 * num_pieces,
+            empty_board: empty_board,
+            slots: slots,
+            goal_idxs: (0..num_pieces).collect(),
+            pending: Vec::new(),
+        };
+    }
+}
+
+impl Iterator for RetrogradeFiller {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(board) = self.pending.pop() {
+                return Some(board);
+            }
+            if self.done {
+                return None;
+            }
+
+            let mut goal_board = self.empty_board.clone();
+            for idx in &self.goal_idxs {
+                let (goal_i, goal_j) = self.slots[*idx];
+                goal_board.set(goal_i, goal_j, Cell::Goal);
+            }
+            self.done = !next_combination(&mut self.goal_idxs, self.slots.len());
+
+            self.pending = retrograde::hardest(&goal_board);
+            // The solved board alone means nothing can reach these goals.
+            self.pending.retain(|board| !board.is_solved());
+            // Popped from the back, so reverse to keep the search order.
+            self.pending.reverse();
+        }
+    }
+}
+
+/// Like SerialFiller, then places the player on every floor cell left free.
+pub struct SerialPushFiller {
+    filler: SerialFiller,
+    /// Last board returned by filler, waiting for a player.
+    current: Option<Board>,
+    /// Free cells of the current board not yet used for the player.
+    player_slots: Vec<(usize, usize)>,
+}
+
+impl SecondaryIter for SerialPushFiller {
+    fn new_secondary(empty_board: Board) -> SerialPushFiller {
+        return SerialPushFiller::new_secondary_multi(empty_board, 1, 0);
+    }
+
+    /// The seed is unused: the order is always the same.
+    fn new_secondary_multi(empty_board: Board, num_pieces: usize, seed: u64) -> SerialPushFiller {
+        return SerialPushFiller {
+            filler: SerialFiller::new_secondary_multi(empty_board, num_pieces, seed),
+            current: None,
+            player_slots: Vec::new(),
+        };
+    }
+}
+
+impl Iterator for SerialPushFiller {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(board) = &self.current {
+                if let Some((x, y)) = self.player_slots.pop() {
+                    let mut filled_board = board.clone();
+                    filled_board.set(x, y, Cell::Player);
+                    return Some(filled_board);
+                }
+            }
+
+            let board = self.filler.next()?;
+            // Reversed, so popping yields the slots in row-major order.
+            self.player_slots = get_slots(&board);
+            self.player_slots.reverse();
+            self.current = Some(board);
+        }
+    }
+}
+
 fn get_slots(board: &Board) -> Vec<(usize, usize)> {
     let mut slots: Vec<(usize, usize)> = Vec::new();
-    let size = board.len();
 
-    for j in 0..size {
-        for i in 0..size {
-            if let Cell::Floor = board[j][i] {
-                slots.push((i, j));
-            }
+    for (pos, cell) in board.iter() {
+        if let Cell::Floor = cell {
+            slots.push(pos);
         }
     }
 
     return slots;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_filler_picks_the_longest_goals() {
+        let mut empty: Board = "....\n.X..\n....\n".parse().unwrap();
+        empty.set(3, 0, Cell::Wall);
+        for num_pieces in 1..=2 {
+            let longest: Vec<Board> = LongestFiller::new_secondary_multi(empty.clone(), num_pieces, 0).collect();
+            let mut layouts = 0;
+            let mut boards = SerialFiller::new_secondary_multi(empty.clone(), num_pieces, 0).peekable();
+            while let Some(first) = boards.next() {
+                // Every goal placement for this piece layout.
+                let pieces = first.piece_set();
+                let mut group = vec![first];
+                while let Some(board) = boards.next_if(|board| board.piece_set() == pieces) {
+                    group.push(board);
+                }
+
+                let best = group.iter()
+                    .map(explore_space)
+                    .filter(|stats| stats.solvable)
+                    .map(|stats| stats.num_moves)
+                    .max();
+                let best = match best {
+                    Some(best) => best,
+                    None => continue,
+                };
+                let chosen = &longest[layouts];
+                assert_eq!(chosen.piece_set(), pieces);
+                assert_eq!(explore_space(chosen).num_moves, best, "{}", chosen);
+                layouts += 1;
+            }
+            assert_eq!(layouts, longest.len());
+        }
+    }
+
+    #[test]
+    fn rng_filler_places_a_player_for_push_rules() {
+        let empty: Board = "....\n....\n".parse().unwrap();
+        let slide: Vec<Board> = RngFiller::<Slide>::new_secondary_multi(empty.clone(), 2, 1).take(20).collect();
+        let push: Vec<Board> = RngPushFiller::new_secondary_multi(empty.clone(), 2, 1).take(20).collect();
+        for board in slide.iter().chain(&push) {
+            assert_eq!((board.piece_set().len(), board.goal_set().len()), (2, 2), "{}", board);
+        }
+        assert!(slide.iter().all(|board| board.player().is_none()));
+        assert!(push.iter().all(|board| board.player().is_some()));
+        assert_eq!(RngFiller::<Slide>::new_secondary_multi(empty, 2, 1).count(), MAX_DRAWS);
+    }
+}
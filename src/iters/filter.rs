@@ -1,33 +1,47 @@
+use std::collections::HashSet;
+
 use crate::base::{Board, BaseIter, SecondaryIter, WrapperIter};
 use crate::tools::symmetry;
 
+/// Boards in a row Symmetries discards, per board accepted so far, before giving up.
+///
+/// A board has at most 8 symmetries, so a source that never repeats a board can't exceed this.
+pub const MAX_MISSES_PER_BOARD: usize = 100;
+
 /// Accumulating filter. Discards boards if they correspond to a symmetry of a previously accepted board.
+///
+/// Random sources over small boards eventually run out of new boards: the filter ends once it discards
+/// MAX_MISSES_PER_BOARD boards in a row for every board it accepted.
 pub struct Symmetries<T: Iterator<Item = Board>> {
     /// Source iterator.
     source: T,
-    /// All boards that have been accepted by the filter.
-    boards: Vec<Board>,
-    /// D4 symmetries of the boards accepted by the filter. symmetries[i] corresponds to boards[i], etc.
-    symmetries: Vec<Vec<Board>>,
+    /// Canonical forms (see symmetry::canonical()) of all boards accepted by the filter.
+    seen: HashSet<Board>,
 }
 
 impl<T: Iterator<Item = Board>> WrapperIter<T> for Symmetries<T> {
     fn new_wrapper(source: T) -> Symmetries<T> {
         return Symmetries {
             source: source,
-            boards: Vec::new(),
-            symmetries: Vec::new(),
+            seen: HashSet::new(),
         };
     }
 }
 
 impl<T: BaseIter> BaseIter for Symmetries<T> {
-    fn new_base(size: u8) -> Symmetries<T> {
-        let source = T::new_base(size);
+    fn new_rect(width: u8, height: u8) -> Symmetries<T> {
+        let source = T::new_rect(width, height);
+        return Symmetries {
+            source: source,
+            seen: HashSet::new(),
+        };
+    }
+
+    fn new_seeded(width: u8, height: u8, seed: u64) -> Symmetries<T> {
+        let source = T::new_seeded(width, height, seed);
         return Symmetries {
             source: source,
-            boards: Vec::new(),
-            symmetries: Vec::new(),
+            seen: HashSet::new(),
         };
     }
 }
@@ -37,8 +51,23 @@ impl<T: SecondaryIter> SecondaryIter for Symmetries<T> {
         let source = T::new_secondary(board);
         return Symmetries {
             source: source,
-            boards: Vec::new(),
-            symmetries: Vec::new(),
+            seen: HashSet::new(),
+        };
+    }
+
+    fn new_secondary_seeded(board: Board, seed: u64) -> Symmetries<T> {
+        let source = T::new_secondary_seeded(board, seed);
+        return Symmetries {
+            source: source,
+            seen: HashSet::new(),
+        };
+    }
+
+    fn new_secondary_multi(board: Board, num_pieces: usize, seed: u64) -> Symmetries<T> {
+        let source = T::new_secondary_multi(board, num_pieces, seed);
+        return Symmetries {
+            source: source,
+            seen: HashSet::new(),
         };
     }
 }
@@ -47,20 +76,8 @@ impl<T: Iterator<Item = Board>> Iterator for Symmetries<T> {
     type Item = Board;
 
     fn next(&mut self) -> Option<Self::Item> {
-        'outer: while let Some(board) = self.source.next() {
-            for set in &self.symmetries {
-                for test in set {
-                    if *test == board {
-                        continue 'outer;
-                    }
-                }
-            }
-
-            self.boards.push(board.clone());
-            self.symmetries.push(symmetry::all(&board));
-            return Some(board);
-        }
-
-        return None;
+        let seen = &mut self.seen;
+        let max_draws = MAX_MISSES_PER_BOARD * (seen.len() + 1) + 1;
+        return self.source.by_ref().take(max_draws).find(|board| seen.insert(symmetry::canonical(board)));
     }
 }
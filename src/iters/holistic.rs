@@ -1,11 +1,16 @@
 use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
 
 use crate::base::{Board, BaseIter, SecondaryIter};
+use crate::difficulty;
 
 use crate::iters::empty::{SerialMaker, RngMaker};
-use crate::iters::filled::{SerialFiller, RngFiller};
+use crate::iters::filled::{SerialFiller, RngFiller, LongestFiller, RetrogradeFiller, SerialPushFiller, RngPushFiller};
 use crate::iters::filter::Symmetries;
-use crate::iters::solvable::Solvable;
+use crate::iters::solvable::{Solvable, PushSolvable, MoveRange, PushMoveRange};
 
 pub fn serial(size: u8) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<SerialFiller>> {
     ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<SerialFiller>>::new(size).unwrap()
@@ -23,18 +28,119 @@ pub fn solvable_random(size: u8) -> ConsumingConnector<Symmetries<RngMaker>, Sym
     ConsumingConnector::<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>>::new(size)
 }
 
+pub fn serial_rect(width: u8, height: u8) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<SerialFiller>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<SerialFiller>>::new_rect(width, height).unwrap()
+}
+
+pub fn random_rect(width: u8, height: u8) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<RngFiller>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<RngFiller>>::new_rect(width, height)
+}
+
+pub fn solvable_serial_rect(width: u8, height: u8) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<Solvable<SerialFiller>>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<Solvable<SerialFiller>>>::new_rect(width, height).unwrap()
+}
+
+pub fn solvable_random_rect(width: u8, height: u8) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>>::new_rect(width, height)
+}
+
+/// Like random_rect(), but the same seed always yields the same boards.
+pub fn random_seeded(width: u8, height: u8, seed: u64) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<RngFiller>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<RngFiller>>::new_seeded(width, height, seed)
+}
+
+/// Like solvable_random_rect(), but the same seed always yields the same boards.
+pub fn solvable_random_seeded(width: u8, height: u8, seed: u64) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>>::new_seeded(width, height, seed)
+}
+
+/// Like serial_rect(), with the given number of pieces and goals on each board.
+pub fn serial_multi(width: u8, height: u8, num_pieces: usize) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<SerialFiller>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<SerialFiller>>::new_multi(width, height, num_pieces).unwrap()
+}
+
+/// Like random_seeded(), with the given number of pieces and goals on each board.
+pub fn random_multi(width: u8, height: u8, num_pieces: usize, seed: u64) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<RngFiller>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<RngFiller>>::new_multi(width, height, num_pieces, seed)
+}
+
+/// Like solvable_serial_rect(), with the given number of pieces and goals on each board.
+pub fn solvable_serial_multi(width: u8, height: u8, num_pieces: usize) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<Solvable<SerialFiller>>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<Solvable<SerialFiller>>>::new_multi(width, height, num_pieces).unwrap()
+}
+
+/// Like solvable_random_seeded(), with the given number of pieces and goals on each board.
+pub fn solvable_random_multi(width: u8, height: u8, num_pieces: usize, seed: u64) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>>::new_multi(width, height, num_pieces, seed)
+}
+
+/// Like solvable_serial_multi(), with only the longest goal placement for each piece layout. See LongestFiller.
+pub fn longest_serial(width: u8, height: u8, num_pieces: usize) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<LongestFiller>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<LongestFiller>>::new_multi(width, height, num_pieces).unwrap()
+}
+
+/// Every empty board and goal placement, with the pieces as far from a solution as possible. See RetrogradeFiller.
+pub fn hardest_serial(width: u8, height: u8, num_pieces: usize) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<RetrogradeFiller>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<RetrogradeFiller>>::new_multi(width, height, num_pieces).unwrap()
+}
+
+/// Like solvable_random_multi(), keeping only boards whose shortest solution length lies in moves.
+pub fn solvable_random_moves(width: u8, height: u8, num_pieces: usize, seed: u64, moves: RangeInclusive<usize>) -> MoveRange<ConsumingConnector<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>>> {
+    MoveRange::new(solvable_random_multi(width, height, num_pieces, seed), moves)
+}
+
+/// Like serial_multi(), with a player for push rules (see crate::push).
+pub fn serial_push(width: u8, height: u8, num_pieces: usize) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<SerialPushFiller>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<SerialPushFiller>>::new_multi(width, height, num_pieces).unwrap()
+}
+
+/// Like random_multi(), with a player for push rules (see crate::push).
+pub fn random_push(width: u8, height: u8, num_pieces: usize, seed: u64) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<RngPushFiller>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<RngPushFiller>>::new_multi(width, height, num_pieces, seed)
+}
+
+/// Like serial_push(), keeping only boards solvable under push rules.
+pub fn solvable_serial_push(width: u8, height: u8, num_pieces: usize) -> ExhaustiveConnector<Symmetries<SerialMaker>, Symmetries<PushSolvable<SerialPushFiller>>> {
+    ExhaustiveConnector::<Symmetries<SerialMaker>, Symmetries<PushSolvable<SerialPushFiller>>>::new_multi(width, height, num_pieces).unwrap()
+}
+
+/// Like random_push(), keeping only boards solvable under push rules.
+pub fn solvable_random_push(width: u8, height: u8, num_pieces: usize, seed: u64) -> ConsumingConnector<Symmetries<RngMaker>, Symmetries<PushSolvable<RngPushFiller>>> {
+    ConsumingConnector::<Symmetries<RngMaker>, Symmetries<PushSolvable<RngPushFiller>>>::new_multi(width, height, num_pieces, seed)
+}
+
+/// Like solvable_random_push(), keeping only boards whose shortest push solution length lies in moves.
+pub fn solvable_push_moves(width: u8, height: u8, num_pieces: usize, seed: u64, moves: RangeInclusive<usize>) -> PushMoveRange<ConsumingConnector<Symmetries<RngMaker>, Symmetries<PushSolvable<RngPushFiller>>>> {
+    PushMoveRange::new(solvable_random_push(width, height, num_pieces, seed), moves)
+}
+
+/// Like solvable_random_multi(), keeping only boards whose difficulty score lies in [min_score, max_score].
+pub fn rated_random(width: u8, height: u8, num_pieces: usize, seed: u64, min_score: f64, max_score: f64) -> DifficultyBand<ConsumingConnector<Symmetries<RngMaker>, Symmetries<Solvable<RngFiller>>>> {
+    DifficultyBand::new(solvable_random_multi(width, height, num_pieces, seed), min_score, max_score)
+}
+
 pub struct ExhaustiveConnector<T: BaseIter, U: SecondaryIter> {
     base: T,
     modifier: U,
+    /// Number of pieces (and goals) placed by each modifier.
+    num_pieces: usize,
 }
 
 impl<T: BaseIter, U: SecondaryIter> ExhaustiveConnector<T, U> {
     pub fn new(size: u8) -> Option<Self> {
-        let mut base = T::new_base(size);
+        Self::new_rect(size, size)
+    }
+
+    pub fn new_rect(width: u8, height: u8) -> Option<Self> {
+        Self::new_multi(width, height, 1)
+    }
+
+    pub fn new_multi(width: u8, height: u8, num_pieces: usize) -> Option<Self> {
+        let mut base = T::new_rect(width, height);
         let board = base.next()?;
-        let modifier = U::new_secondary(board);
+        let modifier = U::new_secondary_multi(board, num_pieces, rand::random());
 
-        return Some(ExhaustiveConnector { base, modifier });
+        return Some(ExhaustiveConnector { base, modifier, num_pieces });
     }
 }
 
@@ -46,7 +152,7 @@ impl<T: BaseIter, U: SecondaryIter> Iterator for ExhaustiveConnector<T, U> {
             if let Some(out) = self.modifier.next() {
                 return Some(out);
             } else if let Some(empty) = self.base.next() {
-                self.modifier = U::new_secondary(empty);
+                self.modifier = U::new_secondary_multi(empty, self.num_pieces, rand::random());
                 continue;
             } else {
                 return None;
@@ -55,15 +161,45 @@ impl<T: BaseIter, U: SecondaryIter> Iterator for ExhaustiveConnector<T, U> {
     }
 }
 
+/// Number of empty boards in a row ConsumingConnector tries to fill before giving up.
+pub const MAX_EMPTY_DRAWS: usize = 1000;
+
+/// Fills boards from an endless random base.
+///
+/// Stops after MAX_EMPTY_DRAWS empty boards in a row that the modifier can't fill, or at once if the
+/// pieces and goals can't fit on a board of this size.
 pub struct ConsumingConnector<T: BaseIter, U: SecondaryIter> {
     base: T,
+    /// Source of seeds for each new modifier.
+    rng: Pcg64,
+    /// Number of pieces (and goals) placed by each modifier.
+    num_pieces: usize,
+    /// Number of cells of each board, walls included.
+    num_cells: usize,
     phantom: PhantomData<U>,
 }
 
 impl<T: BaseIter, U: SecondaryIter> ConsumingConnector<T, U> {
     pub fn new(size: u8) -> Self {
+        Self::new_rect(size, size)
+    }
+
+    pub fn new_rect(width: u8, height: u8) -> Self {
+        Self::new_seeded(width, height, rand::random())
+    }
+
+    /// All randomness in base and modifiers is derived from the given seed.
+    pub fn new_seeded(width: u8, height: u8, seed: u64) -> Self {
+        Self::new_multi(width, height, 1, seed)
+    }
+
+    pub fn new_multi(width: u8, height: u8, num_pieces: usize, seed: u64) -> Self {
+        let mut rng = Pcg64::seed_from_u64(seed);
         ConsumingConnector {
-            base: T::new_base(size),
+            base: T::new_seeded(width, height, rng.gen()),
+            rng: rng,
+            num_pieces: num_pieces,
+            num_cells: width as usize * height as usize,
             phantom: PhantomData,
         }
     }
@@ -73,12 +209,55 @@ impl<T: BaseIter, U: SecondaryIter> Iterator for ConsumingConnector<T, U> {
     type Item = Board;
 
     fn next(&mut self) -> Option<Board> {
-        if let Some(empty) = self.base.next() {
-            if let Some(filled) = U::new_secondary(empty).next() {
+        if 2 * self.num_pieces > self.num_cells {
+            return None;
+        }
+
+        // Skip empty boards the modifier can't fill.
+        for empty in self.base.by_ref().take(MAX_EMPTY_DRAWS) {
+            if let Some(filled) = U::new_secondary_multi(empty, self.num_pieces, self.rng.gen()).next() {
                 return Some(filled);
             }
         }
 
         return None;
     }
-}
\ No newline at end of file
+}
+
+/// Wrapper around a Board iterator. Only returns solvable boards with a difficulty score in a given band.
+///
+/// See difficulty::rate().
+pub struct DifficultyBand<T: Iterator<Item = Board>> {
+    /// Source iterator.
+    source: T,
+    /// Lowest accepted score.
+    min_score: f64,
+    /// Highest accepted score.
+    max_score: f64,
+}
+
+impl<T: Iterator<Item = Board>> DifficultyBand<T> {
+    pub fn new(source: T, min_score: f64, max_score: f64) -> Self {
+        DifficultyBand {
+            source: source,
+            min_score: min_score,
+            max_score: max_score,
+        }
+    }
+}
+
+impl<T: Iterator<Item = Board>> Iterator for DifficultyBand<T> {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        for board in self.source.by_ref() {
+            if let Some(rating) = difficulty::rate(&board) {
+                if rating.score >= self.min_score && rating.score <= self.max_score {
+                    return Some(board);
+                }
+            }
+        }
+
+        return None;
+    }
+}
@@ -2,4 +2,5 @@ pub mod empty;
 pub mod filled;
 pub mod filter;
 pub mod solvable;
-pub mod holistic;
\ No newline at end of file
+pub mod holistic;
+pub mod parallel;
\ No newline at end of file
@@ -0,0 +1,158 @@
+//! Multi-threaded version of the holistic pipelines.
+//!
+//! Worker threads take empty boards from a shared base iterator, fill them, drop symmetries, solve
+//! what's left, and send the results back through a channel. Filling and solving one empty board is
+//! a single unit of work, so boards from the same empty board always come out together.
+//!
+//! As in the single-threaded pipelines, symmetries are only checked between boards from the same
+//! empty board: the base iterator already drops symmetric empty boards, so boards from different
+//! ones can't be symmetric. Memory use doesn't grow with the number of boards generated.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+use crate::base::{Board, BaseIter, SecondaryIter};
+use crate::iters::empty::SerialMaker;
+use crate::iters::filled::SerialFiller;
+use crate::iters::filter::Symmetries;
+use crate::play::explore_space;
+
+/// Parallel version of holistic::serial_multi().
+///
+/// With ordered set, boards come out in the same order as the single-threaded version.
+pub fn serial(width: u8, height: u8, num_pieces: usize, workers: usize, ordered: bool) -> Parallel {
+    Parallel::new::<Symmetries<SerialMaker>, SerialFiller>(width, height, num_pieces, workers, ordered, |_| true)
+}
+
+/// Parallel version of holistic::solvable_serial_multi().
+///
+/// With ordered set, boards come out in the same order as the single-threaded version.
+pub fn solvable_serial(width: u8, height: u8, num_pieces: usize, workers: usize, ordered: bool) -> Parallel {
+    Parallel::new::<Symmetries<SerialMaker>, SerialFiller>(width, height, num_pieces, workers, ordered, |board| explore_space(board).solvable)
+}
+
+/// Number of threads the system can run at once, or 1 if unknown.
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, |workers| workers.get())
+}
+
+/// Channel-backed iterator over the boards found by the worker threads.
+pub struct Parallel {
+    /// Boards from each empty board, tagged with the position of that empty board in the base iterator.
+    receiver: Receiver<(usize, Vec<Board>)>,
+    /// Return boards in base iterator order rather than as soon as they're ready.
+    ordered: bool,
+    /// Results received ahead of their turn, by base index. Only used when ordered.
+    waiting: BTreeMap<usize, Vec<Board>>,
+    /// Base index whose boards come next. Only used when ordered.
+    next_idx: usize,
+    /// Boards ready to be returned, in reverse order.
+    ready: Vec<Board>,
+}
+
+impl Parallel {
+    /// Start worker threads running the base iterator T and the modifier U, keeping the boards for
+    /// which keep returns true. Symmetric boards from the same empty board are dropped (see
+    /// Symmetries); T should drop symmetric empty boards for the output to be free of symmetries.
+    ///
+    /// Modifiers are created with the position of their empty board as seed, so the output doesn't
+    /// depend on scheduling when ordered is set.
+    pub fn new<T, U>(width: u8, height: u8, num_pieces: usize, workers: usize, ordered: bool, keep: fn(&Board) -> bool) -> Parallel
+    where
+        T: BaseIter + Send + 'static,
+        U: SecondaryIter,
+    {
+        let base = Arc::new(Mutex::new(T::new_rect(width, height).enumerate()));
+        let workers = workers.max(1);
+        let (sender, receiver) = sync_channel(4 * workers);
+
+        for _ in 0..workers {
+            let base = Arc::clone(&base);
+            let sender = sender.clone();
+            thread::spawn(move || {
+                loop {
+                    let next = base.lock().unwrap().next();
+                    let (idx, empty) = match next {
+                        Some(next) => next,
+                        None => break,
+                    };
+
+                    let mut boards = Vec::new();
+                    for board in Symmetries::<U>::new_secondary_multi(empty, num_pieces, idx as u64) {
+                        if keep(&board) {
+                            boards.push(board);
+                        }
+                    }
+
+                    // The iterator was dropped: nobody is listening anymore.
+                    if sender.send((idx, boards)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        return Parallel {
+            receiver: receiver,
+            ordered: ordered,
+            waiting: BTreeMap::new(),
+            next_idx: 0,
+            ready: Vec::new(),
+        };
+    }
+}
+
+impl Iterator for Parallel {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        loop {
+            if let Some(board) = self.ready.pop() {
+                return Some(board);
+            }
+
+            if self.ordered {
+                if let Some(mut boards) = self.waiting.remove(&self.next_idx) {
+                    self.next_idx += 1;
+                    boards.reverse();
+                    self.ready = boards;
+                    continue;
+                }
+            }
+
+            // Fails once every worker is done and the channel is empty.
+            let (idx, mut boards) = self.receiver.recv().ok()?;
+            if self.ordered {
+                self.waiting.insert(idx, boards);
+            } else {
+                boards.reverse();
+                self.ready = boards;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iters::holistic;
+
+    #[test]
+    fn ordered_matches_serial() {
+        let parallel: Vec<Board> = solvable_serial(4, 4, 1, 4, true).take(2000).collect();
+        let serial: Vec<Board> = holistic::solvable_serial_multi(4, 4, 1).take(2000).collect();
+        assert_eq!(parallel.len(), 2000);
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn unordered_finds_the_same_boards() {
+        let mut parallel: Vec<String> = serial(3, 3, 1, 4, false).map(|board| board.to_string()).collect();
+        let mut serial: Vec<String> = holistic::serial_multi(3, 3, 1).map(|board| board.to_string()).collect();
+        parallel.sort();
+        serial.sort();
+        assert_eq!(parallel, serial);
+    }
+}
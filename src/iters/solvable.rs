@@ -1,42 +1,73 @@
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
 use crate::base::{Board, BaseIter, SecondaryIter, WrapperIter};
-use crate::play::explore_space;
+use crate::rules::{RuleSet, Slide, Push};
 
-/// Wrapper around a Board iterator. Ensures the returned board is solvable.
-pub struct Solvable<T: Iterator<Item = Board>> {
+/// Wrapper around a Board iterator. Ensures the returned board is solvable under the rules R.
+pub struct Solvable<T: Iterator<Item = Board>, R: RuleSet = Slide> {
     /// Source iterator.
     source: T,
+    rules: PhantomData<R>,
 }
 
-impl<T: Iterator<Item = Board>> WrapperIter<T> for Solvable<T> {
+/// Like Solvable, but checks boards under push rules (see crate::push).
+pub type PushSolvable<T> = Solvable<T, Push>;
+
+impl<T: Iterator<Item = Board>, R: RuleSet> WrapperIter<T> for Solvable<T, R> {
     fn new_wrapper(source: T) -> Self {
         Solvable {
             source: source,
+            rules: PhantomData,
         }
     }
 }
 
-impl<T: BaseIter> BaseIter for Solvable<T> {
-    fn new_base(size: u8) -> Self {
+impl<T: BaseIter, R: RuleSet> BaseIter for Solvable<T, R> {
+    fn new_rect(width: u8, height: u8) -> Self {
+        Solvable {
+            source: T::new_rect(width, height),
+            rules: PhantomData,
+        }
+    }
+
+    fn new_seeded(width: u8, height: u8, seed: u64) -> Self {
         Solvable {
-            source: T::new_base(size),
+            source: T::new_seeded(width, height, seed),
+            rules: PhantomData,
         }
     }
 }
 
-impl<T: SecondaryIter> SecondaryIter for Solvable<T> {
+impl<T: SecondaryIter, R: RuleSet> SecondaryIter for Solvable<T, R> {
     fn new_secondary(board: Board) -> Self {
         Solvable {
             source: T::new_secondary(board),
+            rules: PhantomData,
+        }
+    }
+
+    fn new_secondary_seeded(board: Board, seed: u64) -> Self {
+        Solvable {
+            source: T::new_secondary_seeded(board, seed),
+            rules: PhantomData,
+        }
+    }
+
+    fn new_secondary_multi(board: Board, num_pieces: usize, seed: u64) -> Self {
+        Solvable {
+            source: T::new_secondary_multi(board, num_pieces, seed),
+            rules: PhantomData,
         }
     }
 }
 
-impl<T: Iterator<Item = Board>> Iterator for Solvable<T> {
+impl<T: Iterator<Item = Board>, R: RuleSet> Iterator for Solvable<T, R> {
     type Item = Board;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(board) = self.source.next() {
-            let stats = explore_space(&board);
+        for board in self.source.by_ref() {
+            let stats = R::explore_space(&board);
             if stats.solvable {
                 return Some(board);
             }
@@ -45,3 +76,85 @@ impl<T: Iterator<Item = Board>> Iterator for Solvable<T> {
         return None;
     }
 }
+
+/// Boards MoveRange may reject in a row before giving up, so that a range no board reaches ends
+/// the iterator instead of draining an endless random source.
+pub const MAX_RANGE_MISSES: usize = 10_000;
+
+/// Wrapper around a Board iterator. Only returns boards solvable under the rules R whose shortest
+/// solution length lies in the given range.
+pub struct MoveRange<T: Iterator<Item = Board>, R: RuleSet = Slide> {
+    /// Source iterator.
+    source: T,
+    /// Accepted shortest solution lengths.
+    moves: RangeInclusive<usize>,
+    rules: PhantomData<R>,
+}
+
+/// Like MoveRange, but solves boards under push rules (see crate::push).
+pub type PushMoveRange<T> = MoveRange<T, Push>;
+
+impl<T: Iterator<Item = Board>, R: RuleSet> MoveRange<T, R> {
+    pub fn new(source: T, moves: RangeInclusive<usize>) -> Self {
+        MoveRange {
+            source: source,
+            moves: moves,
+            rules: PhantomData,
+        }
+    }
+}
+
+impl<T: Iterator<Item = Board>, R: RuleSet> Iterator for MoveRange<T, R> {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for board in self.source.by_ref().take(MAX_RANGE_MISSES) {
+            let stats = R::explore_space(&board);
+            if stats.solvable && self.moves.contains(&stats.num_moves) {
+                return Some(board);
+            }
+        }
+
+        return None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+    use crate::iters::holistic;
+    use crate::play::explore_space;
+    use crate::push;
+
+    #[test]
+    fn move_range_keeps_lengths_in_range() {
+        let all: Vec<Board> = holistic::serial_multi(3, 3, 1).collect();
+        let kept: Vec<Board> = MoveRange::<_, Slide>::new(all.clone().into_iter(), 2..=3).collect();
+        let expected: Vec<Board> = all.into_iter()
+            .filter(|board| {
+                let stats = explore_space(board);
+                stats.solvable && (2..=3).contains(&stats.num_moves)
+            })
+            .collect();
+        assert!(!kept.is_empty());
+        assert_eq!(kept, expected);
+    }
+
+    #[test]
+    fn push_move_range_uses_push_rules() {
+        // Two pushes under push rules, but a single slide under sliding rules.
+        let board: Board = "P#.@\n".parse().unwrap();
+        assert_eq!(push::explore_space(&board).num_moves, 2);
+        assert_eq!(PushMoveRange::new(iter::once(board.clone()), 2..=2).count(), 1);
+        assert_eq!(MoveRange::<_, Slide>::new(iter::once(board), 2..=2).count(), 0);
+    }
+
+    #[test]
+    fn move_range_gives_up() {
+        let board: Board = "#@\n".parse().unwrap();
+        let mut range = MoveRange::<_, Slide>::new(iter::repeat(board), 5..=9);
+        assert_eq!(range.next(), None);
+    }
+}
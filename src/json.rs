@@ -0,0 +1,137 @@
+//! JSON schema of the types that implement `Serialize` and `Deserialize` with the `serde` feature.
+//!
+//! The schema is stable: fields may be added, but existing ones keep their names and meaning.
+//!
+//! * `Cell`: the variant name as a string, e.g. `"Floor"`, `"PieceOnGoal"`.
+//! * `Direction`: the variant name as a string: `"Up"`, `"Right"`, `"Down"` or `"Left"`.
+//! * `Board`: dimensions and rows in native text notation (see `text`), top to bottom.
+//!
+//!   ```json
+//!   { "width": 3, "height": 2, "rows": ["#.@", "X.."] }
+//!   ```
+//!
+//! * `MoveStats`: positions are `[x, y]` pairs.
+//!
+//!   ```json
+//!   { "cells_moved": 2, "piece_positions": [[2, 0]], "victory": true }
+//!   ```
+//!
+//! * `ExploreStats`:
+//!
+//!   ```json
+//!   { "solvable": true, "num_moves": 2, "solution": ["Right", "Down"] }
+//!   ```
+//!
+//! * `pack::Level` and `pack::Pack`: optional fields are `null` when missing.
+//!
+//!   ```json
+//!   {
+//!     "title": "Pack title",
+//!     "levels": [
+//!       {
+//!         "title": "1",
+//!         "board": { "width": 3, "height": 1, "rows": ["#.@"] },
+//!         "num_moves": 1,
+//!         "seed": 42,
+//!         "solution": ["Right"]
+//!       }
+//!     ]
+//!   }
+//!   ```
+
+use std::convert::TryFrom;
+
+use serde::{Serialize, Deserialize};
+
+use crate::base::Board;
+use crate::text::{self, ParseError, ParseErrorKind};
+
+/// JSON representation of a Board. See the module documentation.
+#[derive(Serialize, Deserialize)]
+pub struct BoardJson {
+    pub width: u8,
+    pub height: u8,
+    pub rows: Vec<String>,
+}
+
+impl From<Board> for BoardJson {
+    fn from(board: Board) -> BoardJson {
+        let (width, height) = board.dimensions();
+        return BoardJson {
+            width: width as u8,
+            height: height as u8,
+            rows: text::format_board(&board).lines().map(|row| row.to_string()).collect(),
+        };
+    }
+}
+
+impl TryFrom<BoardJson> for Board {
+    type Error = ParseError;
+
+    /// Parse the rows, checking they match the given dimensions.
+    fn try_from(json: BoardJson) -> Result<Board, ParseError> {
+        let board = text::parse_board(&json.rows.join("\n"))?;
+        let expected = (json.width as usize, json.height as usize);
+        if board.dimensions() != expected {
+            let kind = ParseErrorKind::Dimensions { expected: expected, found: board.dimensions() };
+            return Err(ParseError { line: 1, column: 1, kind: kind });
+        }
+
+        return Ok(board);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::base::Board;
+    use crate::pack::{Level, Pack};
+    use crate::play::{explore_space, Direction};
+
+    #[test]
+    fn board_schema() {
+        let board: Board = "#.@\nX.P\n".parse().unwrap();
+        let value = json!({ "width": 3, "height": 2, "rows": ["#.@", "X.P"] });
+        assert_eq!(serde_json::to_value(&board).unwrap(), value);
+        assert_eq!(serde_json::from_value::<Board>(value).unwrap(), board);
+
+        let text = serde_json::to_string(&board).unwrap();
+        assert_eq!(serde_json::from_str::<Board>(&text).unwrap(), board);
+    }
+
+    #[test]
+    fn invalid_boards() {
+        let error = serde_json::from_value::<Board>(json!({ "width": 2, "height": 2, "rows": ["#.", "..@"] })).unwrap_err();
+        assert!(error.to_string().contains("2:1: row has 3 cells, expected 2"), "{}", error);
+
+        let error = serde_json::from_value::<Board>(json!({ "width": 3, "height": 1, "rows": ["#."] })).unwrap_err();
+        assert!(error.to_string().contains("board is 2x1, expected 3x1"), "{}", error);
+
+        assert!(serde_json::from_value::<Board>(json!({ "width": 2, "height": 1 })).is_err());
+    }
+
+    #[test]
+    fn other_schemas() {
+        let board: Board = "#.\n.@\n".parse().unwrap();
+        let stats = json!({ "solvable": true, "num_moves": 2, "solution": ["Down", "Right"] });
+        assert_eq!(serde_json::to_value(explore_space(&board)).unwrap(), stats);
+
+        let mut level = Level::new("1", "#.@\n".parse().unwrap());
+        level.num_moves = Some(1);
+        level.solution = Some(vec![Direction::Right]);
+        let pack = Pack { title: None, levels: vec![level] };
+        let value = json!({
+            "title": null,
+            "levels": [{
+                "title": "1",
+                "board": { "width": 3, "height": 1, "rows": ["#.@"] },
+                "num_moves": 1,
+                "seed": null,
+                "solution": ["Right"],
+            }],
+        });
+        assert_eq!(serde_json::to_value(&pack).unwrap(), value);
+        assert_eq!(serde_json::from_value::<Pack>(value).unwrap(), pack);
+    }
+}
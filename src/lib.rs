@@ -2,6 +2,15 @@ pub mod base;
 pub mod iters;
 pub mod tools;
 pub mod play;
+pub mod push;
+pub mod rules;
+pub mod graph;
+pub mod difficulty;
+pub mod retrograde;
+pub mod text;
+pub mod pack;
+#[cfg(feature = "serde")]
+pub mod json;
 
 // TODO: Put in separate lib to reduce dependencies?
 pub mod colorprint;
\ No newline at end of file
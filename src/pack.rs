@@ -0,0 +1,467 @@
+//! Level packs: collections of boards in formats other Sokoban clients understand.
+//!
+//! Two formats are supported, both using XSB notation for the boards (see `text`):
+//!
+//! * `.sok` / XSB packs: plain text. Optional headers such as `Title:` or `Author:`, then each board
+//!   followed by its fields, with blank lines between levels. Lines starting with `;` are comments.
+//!   Only the `Title` header is kept.
+//!
+//!   ```text
+//!   Title: Pack title
+//!
+//!   ######
+//!   #-$-.#
+//!   ######
+//!   Title: Level 1
+//!   Moves: 1
+//!   Seed: 42
+//!   Solution: R
+//!   ```
+//!
+//! * SLC (`.slc`): the XML format of the SokobanLevels schema. Move count, seed and solution are
+//!   stored as extra `Moves`, `Seed` and `Solution` attributes of each `Level`.
+//!
+//! Other clients expect levels enclosed in walls, so boards are written with a ring of walls around
+//! them. Importers drop the outer ring of any level fully enclosed by walls; since everything
+//! outside a board behaves as a wall anyway, this doesn't change how the level plays.
+
+use std::{error, fmt};
+
+use crate::base::Board;
+use crate::play::Direction;
+use crate::text::{self, Notation, ParseError};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// A board with the information shown to players.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Level {
+    pub title: String,
+    pub board: Board,
+    /// Length of the shortest solution, if known.
+    pub num_moves: Option<usize>,
+    /// Seed the board was generated with, if any.
+    pub seed: Option<u64>,
+    pub solution: Option<Vec<Direction>>,
+}
+
+impl Level {
+    /// A level with no metadata.
+    pub fn new(title: &str, board: Board) -> Level {
+        return Level {
+            title: title.to_string(),
+            board: board,
+            num_moves: None,
+            seed: None,
+            solution: None,
+        };
+    }
+}
+
+/// A titled collection of levels.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pack {
+    pub title: Option<String>,
+    pub levels: Vec<Level>,
+}
+
+/// Reason a pack could not be read.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PackErrorKind {
+    /// A board is malformed. Its position is relative to the first row of the board.
+    Board(ParseError),
+    /// A field has a value that can't be parsed.
+    InvalidValue { key: String, value: String },
+    /// The XML is malformed or doesn't follow the SLC structure.
+    Xml(String),
+}
+
+/// Error returned by the pack readers. Lines start at 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackError {
+    pub line: usize,
+    pub kind: PackErrorKind,
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            PackErrorKind::Board(error) => write!(f, "in board, {}", error),
+            PackErrorKind::InvalidValue { key, value } => write!(f, "invalid {}: {:?}", key, value),
+            PackErrorKind::Xml(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for PackError {}
+
+/// Write a pack in .sok format.
+pub fn write_sok(pack: &Pack) -> String {
+    let mut out = String::new();
+    if let Some(title) = &pack.title {
+        out.push_str(&format!("Title: {}\n\n", title));
+    }
+
+    for (idx, level) in pack.levels.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        for row in enclosed_rows(&level.board) {
+            out.push_str(&row);
+            out.push('\n');
+        }
+
+        out.push_str(&format!("Title: {}\n", level.title));
+        if let Some(num_moves) = level.num_moves {
+            out.push_str(&format!("Moves: {}\n", num_moves));
+        }
+        if let Some(seed) = level.seed {
+            out.push_str(&format!("Seed: {}\n", seed));
+        }
+        if let Some(solution) = &level.solution {
+            out.push_str(&format!("Solution: {}\n", text::format_moves(solution)));
+        }
+    }
+
+    return out;
+}
+
+/// Read a pack in .sok format. Unknown fields and pack headers are ignored.
+///
+/// Levels without a title are named after their position in the pack, starting at 1.
+pub fn read_sok(text: &str) -> Result<Pack, PackError> {
+    let mut pack = Pack::default();
+    // Rows of the board being read, and the line it starts on.
+    let mut rows: Vec<String> = Vec::new();
+    let mut first_line = 0;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if is_board_row(line) {
+            if rows.is_empty() {
+                first_line = idx + 1;
+            }
+            rows.push(line.to_string());
+            continue;
+        }
+
+        if !rows.is_empty() {
+            let board = board_from_rows(&rows, first_line)?;
+            let title = format!("{}", pack.levels.len() + 1);
+            pack.levels.push(Level::new(&title, board));
+            rows.clear();
+        }
+
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        let (key, value) = match line.find(':') {
+            Some(colon) => (line[..colon].trim(), line[colon + 1..].trim()),
+            None => continue,
+        };
+        match pack.levels.last_mut() {
+            Some(level) => set_field(level, key, value).map_err(|kind| PackError { line: idx + 1, kind: kind })?,
+            None if key.eq_ignore_ascii_case("title") => pack.title = Some(value.to_string()),
+            // Other pack headers, such as Author or Copyright.
+            None => (),
+        }
+    }
+
+    if !rows.is_empty() {
+        let board = board_from_rows(&rows, first_line)?;
+        let title = format!("{}", pack.levels.len() + 1);
+        pack.levels.push(Level::new(&title, board));
+    }
+
+    return Ok(pack);
+}
+
+/// Write a pack in SLC format.
+pub fn write_slc(pack: &Pack) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<SokobanLevels>\n");
+    if let Some(title) = &pack.title {
+        out.push_str(&format!("  <Title>{}</Title>\n", escape(title)));
+    }
+
+    let max_width = pack.levels.iter().map(|level| level.board.width() + 2).max().unwrap_or(0);
+    let max_height = pack.levels.iter().map(|level| level.board.height() + 2).max().unwrap_or(0);
+    out.push_str(&format!("  <LevelCollection MaxWidth=\"{}\" MaxHeight=\"{}\">\n", max_width, max_height));
+
+    for level in &pack.levels {
+        let (width, height) = level.board.dimensions();
+        out.push_str(&format!("    <Level Id=\"{}\" Width=\"{}\" Height=\"{}\"", escape(&level.title), width + 2, height + 2));
+        if let Some(num_moves) = level.num_moves {
+            out.push_str(&format!(" Moves=\"{}\"", num_moves));
+        }
+        if let Some(seed) = level.seed {
+            out.push_str(&format!(" Seed=\"{}\"", seed));
+        }
+        if let Some(solution) = &level.solution {
+            out.push_str(&format!(" Solution=\"{}\"", text::format_moves(solution)));
+        }
+        out.push_str(">\n");
+
+        for row in enclosed_rows(&level.board) {
+            // SLC readers expect spaces for floor.
+            out.push_str(&format!("      <L>{}</L>\n", row.replace('-', " ")));
+        }
+        out.push_str("    </Level>\n");
+    }
+
+    out.push_str("  </LevelCollection>\n");
+    out.push_str("</SokobanLevels>\n");
+    return out;
+}
+
+/// Read a pack in SLC format. Elements and attributes other than the ones written by write_slc()
+/// are ignored.
+pub fn read_slc(text: &str) -> Result<Pack, PackError> {
+    let mut pack = Pack::default();
+    // Open elements, innermost last.
+    let mut stack: Vec<String> = Vec::new();
+    let mut content = String::new();
+    // Level being read.
+    let mut level: Option<SlcLevel> = None;
+    let mut pos = 0;
+    // Line of the tag being read, counted up to `counted`.
+    let mut line = 1;
+    let mut counted = 0;
+
+    while let Some(start) = text[pos..].find('<').map(|offset| pos + offset) {
+        content.push_str(&text[pos..start]);
+        line += text[counted..start].matches('\n').count();
+        counted = start;
+        let error = |message: &str| PackError { line: line, kind: PackErrorKind::Xml(message.to_string()) };
+
+        // Declarations and comments carry no data.
+        let skip_to = if text[start..].starts_with("<?") {
+            Some("?>")
+        } else if text[start..].starts_with("<!--") {
+            Some("-->")
+        } else if text[start..].starts_with("<!") {
+            Some(">")
+        } else {
+            None
+        };
+        if let Some(end_marker) = skip_to {
+            let end = text[start..].find(end_marker).ok_or_else(|| error("unterminated markup"))?;
+            pos = start + end + end_marker.len();
+            continue;
+        }
+
+        let end = start + text[start..].find('>').ok_or_else(|| error("unterminated tag"))?;
+        let tag = &text[start + 1..end];
+        pos = end + 1;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            if stack.pop().as_deref() != Some(name) {
+                return Err(error(&format!("unexpected closing tag </{}>", name)));
+            }
+
+            let value = unescape(&content);
+            match (name, stack.last().map(|parent| parent.as_str())) {
+                ("Title", Some("SokobanLevels")) => pack.title = Some(value.trim().to_string()),
+                ("L", Some("Level")) => {
+                    if let Some(level) = &mut level {
+                        level.rows.push(value);
+                    }
+                },
+                ("Level", _) => {
+                    let slc_level = level.take().ok_or_else(|| error("misplaced </Level>"))?;
+                    pack.levels.push(slc_level.build(pack.levels.len())?);
+                },
+                _ => (),
+            }
+            content.clear();
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name = tag.split_whitespace().next().ok_or_else(|| error("empty tag"))?.to_string();
+        if name == "Level" {
+            let attributes = parse_attributes(&tag[name.len()..]).map_err(|message| error(&message))?;
+            level = Some(SlcLevel { attributes: attributes, rows: Vec::new(), first_line: line });
+        }
+        content.clear();
+        if !self_closing {
+            stack.push(name);
+        }
+    }
+
+    if let Some(name) = stack.last() {
+        let line = line + text[counted..].matches('\n').count();
+        return Err(PackError { line: line, kind: PackErrorKind::Xml(format!("unclosed tag <{}>", name)) });
+    }
+
+    return Ok(pack);
+}
+
+/// Contents of an SLC Level element.
+struct SlcLevel {
+    attributes: Vec<(String, String)>,
+    rows: Vec<String>,
+    /// Line of the opening tag, for errors.
+    first_line: usize,
+}
+
+impl SlcLevel {
+    /// Parse the board and metadata. idx is the position of the level in the pack.
+    fn build(&self, idx: usize) -> Result<Level, PackError> {
+        let board = board_from_rows(&self.rows, self.first_line + 1)?;
+        let mut level = Level::new(&format!("{}", idx + 1), board);
+        for (key, value) in &self.attributes {
+            let key = if key == "Id" { "Title" } else { key.as_str() };
+            set_field(&mut level, key, value).map_err(|kind| PackError { line: self.first_line, kind: kind })?;
+        }
+
+        return Ok(level);
+    }
+}
+
+/// Parse `key="value"` pairs, with single or double quotes.
+fn parse_attributes(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or_else(|| format!("malformed attribute {:?}", rest))?;
+        let key = rest[..eq].trim().to_string();
+        let value_text = rest[eq + 1..].trim_start();
+        let quote = value_text.chars().next().filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("unquoted attribute {}", key))?;
+        let end = value_text[1..].find(quote).ok_or_else(|| format!("unterminated attribute {}", key))?;
+        attributes.push((key, unescape(&value_text[1..end + 1])));
+        rest = value_text[end + 2..].trim_start();
+    }
+
+    return Ok(attributes);
+}
+
+/// Apply a metadata field to a level. Keys are case insensitive; unknown keys are ignored.
+fn set_field(level: &mut Level, key: &str, value: &str) -> Result<(), PackErrorKind> {
+    let invalid = || PackErrorKind::InvalidValue { key: key.to_string(), value: value.to_string() };
+    match key.to_ascii_lowercase().as_str() {
+        "title" => level.title = value.to_string(),
+        "moves" => level.num_moves = Some(value.parse().map_err(|_| invalid())?),
+        "seed" => level.seed = Some(value.parse().map_err(|_| invalid())?),
+        "solution" => level.solution = Some(text::parse_moves(value).map_err(|_| invalid())?),
+        _ => (),
+    }
+
+    return Ok(());
+}
+
+/// True if the line is part of a board in XSB notation.
+fn is_board_row(line: &str) -> bool {
+    return line.contains('#') && line.chars().all(|c| " -_#$.*@+".contains(c));
+}
+
+/// Rows of the board in XSB notation, with a ring of walls around them.
+fn enclosed_rows(board: &Board) -> Vec<String> {
+    let border = "#".repeat(board.width() + 2);
+    let mut rows = vec![border.clone()];
+    for line in text::format_board_with(board, Notation::Xsb).lines() {
+        rows.push(format!("#{}#", line));
+    }
+    rows.push(border);
+
+    return rows;
+}
+
+/// Parse XSB rows into a board, dropping the outer ring if it's all walls. first_line is the line
+/// of the first row, for errors.
+fn board_from_rows(rows: &[String], first_line: usize) -> Result<Board, PackError> {
+    let is_wall_row = |row: &String| !row.is_empty() && row.chars().all(|c| c == '#');
+    let enclosed = rows.len() > 2
+        && is_wall_row(&rows[0])
+        && is_wall_row(&rows[rows.len() - 1])
+        && rows.iter().all(|row| row.len() > 2 && row.starts_with('#') && row.ends_with('#'));
+
+    let inner: Vec<&str> = if enclosed {
+        rows[1..rows.len() - 1].iter().map(|row| &row[1..row.len() - 1]).collect()
+    } else {
+        rows.iter().map(|row| row.as_str()).collect()
+    };
+
+    // Rows of spaces would look like blank lines to the parser.
+    let inner: Vec<String> = inner.iter().map(|row| row.replace(' ', "-")).collect();
+    return text::parse_board_with(&inner.join("\n"), Notation::Xsb)
+        .map_err(|error| PackError { line: first_line, kind: PackErrorKind::Board(error) });
+}
+
+/// Escape the characters XML reserves.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Undo escape().
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Pack {
+        let mut first = Level::new("First & <only>", "X.#\n.P@\n".parse().unwrap());
+        first.num_moves = Some(1);
+        first.seed = Some(42);
+        first.solution = Some(vec![Direction::Right]);
+        let second = Level::new("2", ".*.\n".parse().unwrap());
+        return Pack { title: Some("Sample \"pack\"".to_string()), levels: vec![first, second] };
+    }
+
+    #[test]
+    fn sok_round_trip() {
+        let pack = sample();
+        let text = write_sok(&pack);
+        assert!(text.contains("#####\n##-$#\n#-@.#\n#####\nTitle: First & <only>\n"));
+        assert_eq!(read_sok(&text).unwrap(), pack);
+    }
+
+    #[test]
+    fn sok_headers() {
+        let text = "Title: Classics\nAuthor: Someone\nCopyright: none\n\n####\n#$.#\n####\nAuthor: Else\n";
+        let pack = read_sok(text).unwrap();
+        assert_eq!(pack.title.as_deref(), Some("Classics"));
+        assert_eq!(pack.levels, vec![Level::new("1", "#@\n".parse().unwrap())]);
+    }
+
+    #[test]
+    fn slc_round_trip() {
+        let pack = sample();
+        let text = write_slc(&pack);
+        assert!(text.contains("<Title>Sample &quot;pack&quot;</Title>"));
+        assert_eq!(read_slc(&text).unwrap(), pack);
+    }
+
+    #[test]
+    fn error_lines() {
+        let error = read_sok("Title: Pack\n\n###\n#$.#\nMoves: many\n").unwrap_err();
+        assert_eq!(error.line, 5);
+        assert_eq!(error.kind, PackErrorKind::InvalidValue { key: "Moves".to_string(), value: "many".to_string() });
+
+        let error = read_slc("<SokobanLevels>\n<Title>\nPack</Title>\n\n</Level>\n").unwrap_err();
+        assert_eq!(error, PackError { line: 5, kind: PackErrorKind::Xml("unexpected closing tag </Level>".to_string()) });
+
+        let error = read_slc("<SokobanLevels>\n<LevelCollection>\n\n").unwrap_err();
+        assert_eq!(error.line, 4);
+    }
+}
@@ -1,69 +1,120 @@
-use crate::base::{Cell, Board, is_wall, is_goal, find_cell};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::base::{Board, CellSet, is_wall};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Direction { Up, Right, Down, Left }
 
+/// Every direction, in the order the solvers try them.
+pub const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+impl Direction {
+    /// Change in (x, y) when moving one cell in this direction.
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up    => ( 0, -1),
+            Direction::Right => ( 1,  0),
+            Direction::Down  => ( 0,  1),
+            Direction::Left  => (-1,  0),
+        }
+    }
+}
+
+/// Position one cell away from pos in the given direction, or None if it falls outside the board.
+pub fn step(board: &Board, pos: (u8, u8), direction: Direction) -> Option<(u8, u8)> {
+    let (width, height) = board.dimensions();
+    let delta = direction.delta();
+    let new_pos = ((pos.0 as i32 + delta.0) as u8, (pos.1 as i32 + delta.1) as u8);
+
+    // NOTE: This depends on uint overflow to work correctly!
+    if new_pos.0 as usize >= width || new_pos.1 as usize >= height {
+        return None;
+    }
+
+    return Some(new_pos);
+}
+
 /// Object returned by move_piece(), summarizing board status information.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MoveStats {
-    /// Number of cells the piece traveled.
-    pub cells_moved: u8,
-    /// (x,y) indices for the piece position, after moving.
-    pub piece_pos: (u8, u8),
-    /// True if this move won the game (i.e., moved the piece into the goal).
+    /// Number of cells the pieces traveled, added up.
+    pub cells_moved: usize,
+    /// (x,y) indices for the piece positions, after moving, in row-major order.
+    pub piece_positions: Vec<(u8, u8)>,
+    /// True if this move won the game (i.e., every goal is now covered by a piece).
     pub victory: bool,
 }
 
-/// Try moving the piece in the given direction, and return stats for the board status after moving.
+/// Try moving the pieces in the given direction, and return stats for the board status after moving.
+///
+/// All pieces slide at once. Each one stops against a wall, the border or another piece,
+/// or when it reaches a goal.
 ///
 /// Returns None if there is no piece to move; Some(stats) otherwise.
 pub fn move_piece(board: &mut Board, direction: Direction) -> Option<MoveStats> {
-    let size = board.len() as u8;
+    let pieces = board.piece_set();
+    if pieces.is_empty() {
+        return None;
+    }
 
-    if let Some(init_pos) = find_cell(board, Cell::Piece) {
-        let delta: (i32, i32) = match direction {
-            Direction::Up    => ( 0, -1),
-            Direction::Right => ( 1,  0),
-            Direction::Down  => ( 0,  1),
-            Direction::Left  => (-1,  0),
-        };
-        let mut pos = init_pos;
-        let mut num_cells = 0;
-        let mut victory = false;
+    let (pieces, cells_moved) = tilt(board, pieces, direction);
+    board.set_pieces(pieces);
 
-        loop {
-            let new_pos = ((pos.0 as i32 + delta.0) as u8, (pos.1 as i32 + delta.1) as u8);
+    return Some(MoveStats {
+        cells_moved: cells_moved,
+        piece_positions: board.pieces(),
+        victory: board.is_solved(),
+    });
+}
 
-            // NOTE: This depends on uint overflow to work correctly!
-            if new_pos.0 >= size || new_pos.1 >= size {
-                break;
-            }
+/// Slide the given pieces in a direction. Returns the new piece positions and the number of cells traveled.
+///
+/// Does not modify the board; only its walls and goals are taken into account.
+pub(crate) fn tilt(board: &Board, pieces: CellSet, direction: Direction) -> (CellSet, usize) {
+    let goals = board.goal_set();
+
+    // Pieces closest to the border they're moving towards go first, so the rest stop against them.
+    let order: Vec<usize> = match direction {
+        Direction::Up | Direction::Left => pieces.iter().collect(),
+        Direction::Down | Direction::Right => pieces.iter().rev().collect(),
+    };
+
+    let mut moved = pieces;
+    let mut num_cells = 0;
 
-            let cell = board[new_pos.1 as usize][new_pos.0 as usize];
+    for idx in order {
+        moved.remove(idx);
+        let mut pos = board.position(idx);
 
-            if is_wall(cell) {
+        while let Some(new_pos) = step(board, pos, direction) {
+            let new_idx = board.cell_index(new_pos.0 as usize, new_pos.1 as usize);
+
+            if is_wall(board[(new_pos.0 as usize, new_pos.1 as usize)]) || moved.contains(new_idx) {
                 break;
             }
 
             pos = new_pos;
             num_cells += 1;
 
-            if is_goal(cell) {
-                victory = true;
+            if goals.contains(new_idx) {
                 break;
             }
         }
 
-        board[init_pos.1 as usize][init_pos.0 as usize] = Cell::Floor;
-        board[pos.1 as usize][pos.0 as usize] = Cell::Piece;
-
-        return Some(MoveStats { cells_moved: num_cells, piece_pos: pos, victory: victory});
+        moved.insert(board.cell_index(pos.0 as usize, pos.1 as usize));
     }
-    
-    return None;
+
+    return (moved, num_cells);
 }
 
 /// Summary of recursive exploration of the move space of a board.
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExploreStats {
     /// Can this board be solved?
     pub solvable: bool,
@@ -71,53 +122,57 @@ pub struct ExploreStats {
     pub solution: Vec<Direction>,
 }
 
-/// Tree structure around a sequence of moves.
-struct TreeNode {
-    board: Board,
-    reached_by: Option<Direction>,
-    parent_idx: Option<usize>,
+/// Search state. Walls and goals never move, so the piece positions are enough to rebuild the board.
+type State = CellSet;
+
+/// Tree structure around a sequence of moves. Generic over the search state, so other rule sets can reuse it.
+pub(crate) struct TreeNode<S> {
+    pub(crate) state: S,
+    pub(crate) reached_by: Option<Direction>,
+    pub(crate) parent_idx: Option<usize>,
 }
 
-struct Tree {
-    nodes: Vec<TreeNode>,
+pub(crate) struct Tree<S> {
+    nodes: Vec<TreeNode<S>>,
+    /// States present in nodes, for constant time lookup.
+    visited: HashSet<S>,
 }
 
-impl Tree {
-    /// Create a new tree from a board.
-    fn new(board: Board) -> Tree {
+impl<S: Copy + Eq + Hash> Tree<S> {
+    /// Create a new tree from an initial state.
+    pub(crate) fn new(state: S) -> Tree<S> {
+        let mut visited = HashSet::new();
+        visited.insert(state);
         Tree {
             nodes: vec![TreeNode {
-                board: board, 
+                state: state, 
                 reached_by: None, 
                 parent_idx: None
             }],
+            visited: visited,
         }
     }
 
-    fn get(&self, idx: usize) -> &TreeNode {
+    pub(crate) fn get(&self, idx: usize) -> &TreeNode<S> {
         &self.nodes[idx]
     }
 
-    fn push(&mut self, node: TreeNode) {
+    pub(crate) fn push(&mut self, node: TreeNode<S>) {
+        self.visited.insert(node.state);
         self.nodes.push(node)
     }
 
-    fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         self.nodes.len()
     }
 
-    /// Check if self contains a given board in one of its nodes.
-    fn contains_board(&self, board: &Board) -> bool {
-        for node in &self.nodes {
-            if node.board == *board {
-                return true;
-            }
-        }
-        return false;
+    /// Check if self contains a given state in one of its nodes.
+    pub(crate) fn contains_state(&self, state: &S) -> bool {
+        self.visited.contains(state)
     }
 
     /// Unwind the moves needed to obtain a given board position.
-    fn trace_moves(&self, idx: usize) -> Vec<Direction> {
+    pub(crate) fn trace_moves(&self, idx: usize) -> Vec<Direction> {
         let mut out: Vec<Direction> = Vec::new();
         let mut node = self.get(idx);
     
@@ -136,36 +191,36 @@ impl Tree {
 
 /// Recursively explore all possible moves.
 pub fn explore_space(board: &Board) -> ExploreStats {
-    if let None = find_cell(board, Cell::Piece) {
+    let init_pieces = board.piece_set();
+    if init_pieces.is_empty() || board.goal_set().is_empty() {
         return Default::default();
     }
+    if board.is_solved() {
+        return ExploreStats { solvable: true, ..Default::default() };
+    }
 
-    let mut tree = Tree::new(board.clone());
+    let mut tree: Tree<State> = Tree::new(init_pieces);
+    let goals = board.goal_set();
     let mut idx = 0;
 
     while idx < tree.len() {
-        for dir in &[Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+        for dir in &DIRECTIONS {
             let node = tree.get(idx);
-            if Some(*dir) == node.reached_by {
-                continue;
-            }
+            let (pieces, cells_moved) = tilt(board, node.state, *dir);
 
-            let mut candidate_board = node.board.clone();
-            let stats = move_piece(&mut candidate_board, *dir).unwrap();
-
-            if stats.cells_moved == 0 || tree.contains_board(&candidate_board) {
+            if cells_moved == 0 || tree.contains_state(&pieces) {
                 continue;
             }
 
             let new_node = TreeNode { 
-                board: candidate_board, 
+                state: pieces, 
                 reached_by: Some(*dir), 
                 parent_idx: Some(idx), 
             };
             tree.push(new_node);
             let new_idx = &tree.len() - 1;
 
-            if stats.victory {
+            if pieces.is_superset(&goals) {
                 let solution = tree.trace_moves(new_idx);
                 return ExploreStats { 
                     solvable: true, 
@@ -179,4 +234,329 @@ pub fn explore_space(board: &Board) -> ExploreStats {
     }
 
     return Default::default();
-}
\ No newline at end of file
+}
+
+/// Every shortest solution of a board. See explore_optimal().
+#[derive(Default, Debug)]
+pub struct OptimalStats {
+    /// Can this board be solved?
+    pub solvable: bool,
+    /// Length of the shortest solutions.
+    pub num_moves: usize,
+    /// Number of distinct shortest move sequences. Saturates at u64::MAX.
+    pub num_solutions: u64,
+    /// Every shortest move sequence, sorted. Empty if not requested.
+    pub solutions: Vec<Vec<Direction>>,
+}
+
+/// Breadth-first layers of the move space, down to the first layer containing a solved state.
+struct Layers {
+    states: Vec<State>,
+    /// Every (parent index, direction) leading to each state from the previous layer.
+    parents: Vec<Vec<(usize, Direction)>>,
+    /// Number of shortest move sequences reaching each state.
+    counts: Vec<u64>,
+    /// Indices of the solved states in the last layer.
+    solved: Vec<usize>,
+    num_moves: usize,
+}
+
+impl Layers {
+    /// Explore until a solved state shows up, keeping every parent of every state.
+    /// Returns None if the board can't be solved.
+    fn explore(board: &Board) -> Option<Layers> {
+        let init_pieces = board.piece_set();
+        let goals = board.goal_set();
+        if init_pieces.is_empty() || goals.is_empty() {
+            return None;
+        }
+
+        let mut layers = Layers {
+            states: vec![init_pieces],
+            parents: vec![Vec::new()],
+            counts: vec![1],
+            solved: Vec::new(),
+            num_moves: 0,
+        };
+        if init_pieces.is_superset(&goals) {
+            layers.solved.push(0);
+            return Some(layers);
+        }
+
+        let mut index: HashMap<State, usize> = HashMap::new();
+        index.insert(init_pieces, 0);
+        let mut layer = 0..1;
+
+        while !layer.is_empty() {
+            let next_start = layers.states.len();
+            layers.num_moves += 1;
+
+            for idx in layer {
+                for dir in &DIRECTIONS {
+                    let (pieces, cells_moved) = tilt(board, layers.states[idx], *dir);
+                    if cells_moved == 0 {
+                        continue;
+                    }
+
+                    let new_idx = match index.get(&pieces) {
+                        // Reached earlier: not a shortest path.
+                        Some(&new_idx) if new_idx < next_start => continue,
+                        Some(&new_idx) => new_idx,
+                        None => {
+                            index.insert(pieces, layers.states.len());
+                            layers.states.push(pieces);
+                            layers.parents.push(Vec::new());
+                            layers.counts.push(0);
+                            layers.states.len() - 1
+                        },
+                    };
+
+                    layers.parents[new_idx].push((idx, *dir));
+                    layers.counts[new_idx] = layers.counts[new_idx].saturating_add(layers.counts[idx]);
+                }
+            }
+
+            layer = next_start..layers.states.len();
+            layers.solved = layer.clone().filter(|idx| layers.states[*idx].is_superset(&goals)).collect();
+            if !layers.solved.is_empty() {
+                return Some(layers);
+            }
+        }
+
+        return None;
+    }
+
+    /// Every move sequence from the initial state to idx through the parents.
+    fn paths_to(&self, idx: usize) -> Vec<Vec<Direction>> {
+        if self.parents[idx].is_empty() {
+            return vec![Vec::new()];
+        }
+
+        let mut out = Vec::new();
+        for (parent_idx, dir) in &self.parents[idx] {
+            for mut path in self.paths_to(*parent_idx) {
+                path.push(*dir);
+                out.push(path);
+            }
+        }
+
+        return out;
+    }
+}
+
+/// Count the shortest solutions of a board, without listing them.
+pub fn count_optimal(board: &Board) -> OptimalStats {
+    let layers = match Layers::explore(board) {
+        Some(layers) => layers,
+        None => return Default::default(),
+    };
+
+    let num_solutions = layers.solved.iter().fold(0u64, |sum, idx| sum.saturating_add(layers.counts[*idx]));
+    return OptimalStats {
+        solvable: true,
+        num_moves: layers.num_moves,
+        num_solutions: num_solutions,
+        solutions: Vec::new(),
+    };
+}
+
+/// Find every shortest solution of a board.
+///
+/// The number of solutions can grow exponentially with their length; check count_optimal() first
+/// on boards with long solutions.
+pub fn explore_optimal(board: &Board) -> OptimalStats {
+    let layers = match Layers::explore(board) {
+        Some(layers) => layers,
+        None => return Default::default(),
+    };
+
+    let mut solutions: Vec<Vec<Direction>> = layers.solved.iter().flat_map(|idx| layers.paths_to(*idx)).collect();
+    solutions.sort_by_key(|solution| solution.iter().map(|dir| *dir as u8).collect::<Vec<u8>>());
+    return OptimalStats {
+        solvable: true,
+        num_moves: layers.num_moves,
+        num_solutions: solutions.len() as u64,
+        solutions: solutions,
+    };
+}
+
+/// Count the move sequences of at most max_moves moves that solve a board.
+///
+/// Only moves that change the board are counted, and a sequence ends as soon as the board is solved.
+/// Sequences may go through the same position more than once. Saturates at u64::MAX.
+pub fn count_solutions(board: &Board, max_moves: usize) -> u64 {
+    let init_pieces = board.piece_set();
+    let goals = board.goal_set();
+    if init_pieces.is_empty() || goals.is_empty() {
+        return 0;
+    }
+    if init_pieces.is_superset(&goals) {
+        return 1;
+    }
+
+    // Number of unsolved sequences ending on each state, for the current length.
+    let mut counts: HashMap<State, u64> = HashMap::new();
+    counts.insert(init_pieces, 1);
+    let mut total: u64 = 0;
+
+    for _ in 0..max_moves {
+        let mut next_counts: HashMap<State, u64> = HashMap::new();
+        for (state, count) in &counts {
+            for dir in &DIRECTIONS {
+                let (pieces, cells_moved) = tilt(board, *state, *dir);
+                if cells_moved == 0 {
+                    continue;
+                }
+
+                if pieces.is_superset(&goals) {
+                    total = total.saturating_add(*count);
+                } else {
+                    let entry = next_counts.entry(pieces).or_insert(0);
+                    *entry = entry.saturating_add(*count);
+                }
+            }
+        }
+
+        counts = next_counts;
+    }
+
+    return total;
+}
+
+/// Cells from which a lone piece can never slide onto a goal.
+///
+/// Other pieces are ignored, so with several pieces a cell outside this set can still be a dead end.
+/// Every non-wall cell is dead on a board without goals.
+pub fn dead_cells(board: &Board) -> CellSet {
+    let (width, height) = board.dimensions();
+    let mut floor = CellSet::default();
+    // Cells a piece can slide from to reach each cell, in one move.
+    let mut sources: Vec<Vec<usize>> = vec![Vec::new(); width * height];
+
+    for ((x, y), cell) in board.iter() {
+        if is_wall(cell) {
+            continue;
+        }
+
+        let idx = board.cell_index(x, y);
+        floor.insert(idx);
+        let mut piece = CellSet::default();
+        piece.insert(idx);
+        for direction in &DIRECTIONS {
+            let (moved, _) = tilt(board, piece, *direction);
+            for dest in moved.iter() {
+                sources[dest].push(idx);
+            }
+        }
+    }
+
+    let mut alive = board.goal_set();
+    let mut stack: Vec<usize> = alive.iter().collect();
+    while let Some(idx) = stack.pop() {
+        for &source in &sources[idx] {
+            if !alive.contains(source) {
+                alive.insert(source);
+                stack.push(source);
+            }
+        }
+    }
+
+    let mut dead = floor;
+    for idx in alive.iter() {
+        dead.remove(idx);
+    }
+    return dead;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iters::holistic;
+
+    /// Play moves on a copy of the board.
+    fn replay(board: &Board, moves: &[Direction]) -> Board {
+        let mut board = board.clone();
+        for direction in moves {
+            move_piece(&mut board, *direction);
+        }
+        return board;
+    }
+
+    /// Move once and compare with the expected board.
+    fn check_move(before: &str, direction: Direction, after: &str, cells_moved: usize) {
+        let mut board: Board = before.parse().unwrap();
+        let stats = move_piece(&mut board, direction).unwrap();
+        let expected: Board = after.parse().unwrap();
+        assert_eq!(board, expected, "{:?} from\n{}", direction, before);
+        assert_eq!(stats.cells_moved, cells_moved);
+        assert_eq!(stats.piece_positions, expected.pieces());
+        assert_eq!(stats.victory, expected.is_solved());
+    }
+
+    #[test]
+    fn pieces_stop_against_each_other() {
+        check_move("##..\n", Direction::Right, "..##\n", 4);
+        check_move("#.#.\n", Direction::Left, "##..\n", 1);
+        check_move("#\n.\n#\n.\n", Direction::Down, ".\n.\n#\n#\n", 3);
+        check_move(".\n#\n#\nX\n", Direction::Up, "#\n#\n.\nX\n", 2);
+        // Blocked by the border: nothing moves.
+        check_move("..##\n", Direction::Right, "..##\n", 0);
+    }
+
+    #[test]
+    fn pieces_stop_on_goals() {
+        check_move("#.@..\n", Direction::Right, "..*..\n", 2);
+        // The first piece stops on the goal, the second one against it.
+        check_move("#.#.@.\n", Direction::Right, "...#*.\n", 5);
+        check_move("#\n@\n#\n@\n.\n", Direction::Down, ".\n*\n.\n*\n.\n", 2);
+        // A piece already on a goal slides off it.
+        check_move("*..@\n", Direction::Right, "@..*\n", 3);
+
+        assert!(move_piece(&mut "..@\n".parse().unwrap(), Direction::Left).is_none());
+    }
+
+    #[test]
+    fn optimal_solutions_by_hand() {
+        // The piece can go right then down, or down then right.
+        let board: Board = "#.\n.@\n".parse().unwrap();
+        let stats = explore_optimal(&board);
+        assert!(stats.solvable);
+        assert_eq!(stats.num_moves, 2);
+        assert_eq!(stats.solutions, vec![
+            vec![Direction::Right, Direction::Down],
+            vec![Direction::Down, Direction::Right],
+        ]);
+        assert_eq!(count_optimal(&board).num_solutions, 2);
+        assert_eq!(count_solutions(&board, 1), 0);
+        assert_eq!(count_solutions(&board, 2), 2);
+        // Going back and forth takes two moves: RLRD, RLDR, DURD and DUDR.
+        assert_eq!(count_solutions(&board, 3), 2);
+        assert_eq!(count_solutions(&board, 4), 6);
+    }
+
+    #[test]
+    fn solution_counts_saturate() {
+        // Solved in one move, but the piece can wander around the board for as long as it likes first.
+        let board: Board = ".XX.\n.@#.\nX..X\nX.XX\n".parse().unwrap();
+        assert_eq!(count_optimal(&board).num_solutions, 1);
+        assert_eq!(count_solutions(&board, 200), u64::MAX);
+    }
+
+    #[test]
+    fn optimal_counts_agree() {
+        let boards = holistic::solvable_random_multi(5, 5, 2, 1).take(150)
+            .chain(holistic::solvable_random_multi(5, 5, 3, 2).take(150));
+        for board in boards {
+            let optimal = explore_optimal(&board);
+            let counted = count_optimal(&board);
+            assert_eq!(optimal.num_moves, explore_space(&board).num_moves, "{}", board);
+            assert_eq!(counted.num_moves, optimal.num_moves, "{}", board);
+            assert_eq!(counted.num_solutions, optimal.num_solutions, "{}", board);
+            assert_eq!(count_solutions(&board, optimal.num_moves), optimal.num_solutions, "{}", board);
+            for solution in &optimal.solutions {
+                assert_eq!(solution.len(), optimal.num_moves);
+                assert!(replay(&board, solution).is_solved(), "{}", board);
+            }
+        }
+    }
+}
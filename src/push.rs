@@ -0,0 +1,270 @@
+//! Classic Sokoban rules: a player walks around the board and pushes pieces one cell at a time.
+//!
+//! Unlike the sliding rules in `play`, pieces only move when the player walks into them, and only
+//! if the cell beyond is free. Pieces can't be pulled, so pushing one into a corner loses the game.
+
+use crate::base::{Board, CellSet, is_wall};
+use crate::play::{Direction, DIRECTIONS, ExploreStats, Tree, TreeNode, step};
+
+/// Object returned by move_player(), summarizing board status information.
+pub struct PushStats {
+    /// True if the player changed position.
+    pub moved: bool,
+    /// True if the player pushed a piece.
+    pub pushed: bool,
+    /// (x,y) indices for the player position, after moving.
+    pub player_pos: (u8, u8),
+    /// True if every goal is now covered by a piece.
+    pub victory: bool,
+}
+
+/// Try moving the player in the given direction, pushing the piece in front of it if possible.
+///
+/// Returns None if there is no player on the board; Some(stats) otherwise.
+pub fn move_player(board: &mut Board, direction: Direction) -> Option<PushStats> {
+    let player = board.player()?;
+    let pieces = board.piece_set();
+
+    let (player_pos, moved, pushed) = match walk(board, player, pieces, direction) {
+        Some((new_player, new_pieces)) => {
+            board.set_pieces(new_pieces);
+            board.set_player(Some(new_player));
+            (new_player, true, new_pieces != pieces)
+        },
+        None => (player, false, false),
+    };
+
+    return Some(PushStats {
+        moved: moved,
+        pushed: pushed,
+        player_pos: player_pos,
+        victory: board.is_solved(),
+    });
+}
+
+/// Walk the player one cell in a direction. Returns the new player and piece positions, or None if blocked.
+///
+/// Does not modify the board; only its walls are taken into account.
+fn walk(board: &Board, player: (u8, u8), pieces: CellSet, direction: Direction) -> Option<((u8, u8), CellSet)> {
+    let next = step(board, player, direction)?;
+    if is_wall(board[(next.0 as usize, next.1 as usize)]) {
+        return None;
+    }
+
+    let next_idx = board.cell_index(next.0 as usize, next.1 as usize);
+    let mut pieces = pieces;
+    if pieces.contains(next_idx) {
+        let beyond = step(board, next, direction)?;
+        let beyond_idx = board.cell_index(beyond.0 as usize, beyond.1 as usize);
+        if is_wall(board[(beyond.0 as usize, beyond.1 as usize)]) || pieces.contains(beyond_idx) {
+            return None;
+        }
+
+        pieces.remove(next_idx);
+        pieces.insert(beyond_idx);
+    }
+
+    return Some((next, pieces));
+}
+
+/// True if a piece at pos could never be pushed again: it's blocked both vertically and horizontally.
+fn in_corner(board: &Board, pos: (u8, u8)) -> bool {
+    let blocked = |direction: Direction| match step(board, pos, direction) {
+        Some(next) => is_wall(board[(next.0 as usize, next.1 as usize)]),
+        None => true,
+    };
+
+    return (blocked(Direction::Up) || blocked(Direction::Down))
+        && (blocked(Direction::Left) || blocked(Direction::Right));
+}
+
+/// Search state: player position and piece positions.
+type State = (u8, u8, CellSet);
+
+/// Explore all possible moves breadth-first. Every step counts as a move, pushing or not.
+///
+/// Pushes that leave a piece stuck in a corner away from a goal are pruned.
+pub fn explore_space(board: &Board) -> ExploreStats {
+    let player = match board.player() {
+        Some(player) => player,
+        None => return Default::default(),
+    };
+    let init_pieces = board.piece_set();
+    let goals = board.goal_set();
+    if init_pieces.is_empty() || goals.is_empty() {
+        return Default::default();
+    }
+    if board.is_solved() {
+        return ExploreStats { solvable: true, ..Default::default() };
+    }
+
+    let mut tree: Tree<State> = Tree::new((player.0, player.1, init_pieces));
+    let mut idx = 0;
+
+    while idx < tree.len() {
+        for dir in &DIRECTIONS {
+            let (x, y, pieces) = tree.get(idx).state;
+            let (new_player, new_pieces) = match walk(board, (x, y), pieces, *dir) {
+                Some(result) => result,
+                None => continue,
+            };
+
+            let state = (new_player.0, new_player.1, new_pieces);
+            if tree.contains_state(&state) {
+                continue;
+            }
+
+            if new_pieces != pieces {
+                // The pushed piece is now one step beyond the player.
+                let pushed = step(board, new_player, *dir).unwrap();
+                let pushed_idx = board.cell_index(pushed.0 as usize, pushed.1 as usize);
+                if !goals.contains(pushed_idx) && in_corner(board, pushed) {
+                    continue;
+                }
+            }
+
+            tree.push(TreeNode {
+                state: state,
+                reached_by: Some(*dir),
+                parent_idx: Some(idx),
+            });
+            let new_idx = tree.len() - 1;
+
+            if new_pieces.is_superset(&goals) {
+                let solution = tree.trace_moves(new_idx);
+                return ExploreStats {
+                    solvable: true,
+                    num_moves: solution.len(),
+                    solution: solution,
+                };
+            }
+        }
+
+        idx += 1;
+    }
+
+    return Default::default();
+}
+
+/// Cells from which a lone piece can never be pushed onto a goal.
+///
+/// Other pieces and the player's reach are ignored, so a cell outside this set can still be a dead end.
+/// Every non-wall cell is dead on a board without goals.
+pub fn dead_cells(board: &Board) -> CellSet {
+    let open = |pos: (u8, u8)| !is_wall(board[(pos.0 as usize, pos.1 as usize)]);
+
+    // Work backwards from the goals: a piece reaches pos from the next cell in some direction,
+    // pushed by a player standing one cell further.
+    let mut alive = board.goal_set();
+    let mut stack: Vec<usize> = alive.iter().collect();
+    while let Some(idx) = stack.pop() {
+        let pos = board.position(idx);
+        for direction in &DIRECTIONS {
+            let from = match step(board, pos, *direction) {
+                Some(from) if open(from) => from,
+                _ => continue,
+            };
+            match step(board, from, *direction) {
+                Some(behind) if open(behind) => (),
+                _ => continue,
+            }
+
+            let from_idx = board.cell_index(from.0 as usize, from.1 as usize);
+            if !alive.contains(from_idx) {
+                alive.insert(from_idx);
+                stack.push(from_idx);
+            }
+        }
+    }
+
+    let mut dead = CellSet::default();
+    for ((x, y), cell) in board.iter() {
+        let idx = board.cell_index(x, y);
+        if !is_wall(cell) && !alive.contains(idx) {
+            dead.insert(idx);
+        }
+    }
+    return dead;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashSet, VecDeque};
+
+    use super::*;
+    use crate::iters::holistic;
+
+    /// Shortest solution length by plain breadth-first search over move_player(), without pruning.
+    fn brute_force(board: &Board) -> Option<usize> {
+        let mut seen = HashSet::new();
+        let mut pending = VecDeque::new();
+        seen.insert((board.player(), board.piece_set()));
+        pending.push_back((board.clone(), 0));
+        while let Some((board, num_moves)) = pending.pop_front() {
+            if board.is_solved() {
+                return Some(num_moves);
+            }
+            for dir in &DIRECTIONS {
+                let mut next = board.clone();
+                move_player(&mut next, *dir);
+                if seen.insert((next.player(), next.piece_set())) {
+                    pending.push_back((next, num_moves + 1));
+                }
+            }
+        }
+
+        return None;
+    }
+
+    #[test]
+    fn moves() {
+        let mut board: Board = "P#.\nX#@\n".parse().unwrap();
+        let stats = move_player(&mut board, Direction::Down).unwrap();
+        assert!(!stats.moved);
+        assert_eq!(stats.player_pos, (0, 0));
+
+        let stats = move_player(&mut board, Direction::Right).unwrap();
+        assert!(stats.moved && stats.pushed && !stats.victory);
+        assert_eq!(board, ".P#\nX#@\n".parse().unwrap());
+
+        // Blocked by the border, then by the other piece.
+        assert!(!move_player(&mut board, Direction::Right).unwrap().moved);
+        assert!(!move_player(&mut board, Direction::Down).unwrap().moved);
+
+        assert!(move_player(&mut "#@\n".parse().unwrap(), Direction::Up).is_none());
+    }
+
+    #[test]
+    fn corner_goals_are_not_pruned() {
+        // The last push puts the piece in the corner, on the goal.
+        let board: Board = "...\n.#.\nP.@\n".parse().unwrap();
+        let stats = explore_space(&board);
+        assert!(stats.solvable);
+        assert_eq!(stats.num_moves, brute_force(&board).unwrap());
+
+        let board: Board = "P#@\n".parse().unwrap();
+        assert_eq!(explore_space(&board).solution, vec![Direction::Right]);
+
+        // Here the only push leaves the piece in a corner without a goal.
+        let board: Board = "P#.\n..@\n".parse().unwrap();
+        assert!(!explore_space(&board).solvable);
+        assert_eq!(brute_force(&board), None);
+    }
+
+    #[test]
+    fn solutions_replay() {
+        let boards = holistic::random_push(5, 5, 1, 6).take(150)
+            .chain(holistic::random_push(4, 4, 2, 7).take(150));
+        for board in boards {
+            let stats = explore_space(&board);
+            assert_eq!(if stats.solvable { Some(stats.num_moves) } else { None }, brute_force(&board), "{}", board);
+
+            let mut played = board.clone();
+            for direction in &stats.solution {
+                move_player(&mut played, *direction);
+            }
+            assert_eq!(played.is_solved(), stats.solvable, "{}", board);
+            assert_eq!(stats.solution.len(), stats.num_moves);
+        }
+    }
+}
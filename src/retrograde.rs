@@ -0,0 +1,168 @@
+//! Retrograde generation under the sliding rules (see play): start from the solved position and
+//! walk backwards over "un-slide" moves.
+//!
+//! A single backwards breadth-first search finds every start position that can reach the goals,
+//! along with its optimal distance, so the hardest puzzles for a layout come out in one pass.
+
+use std::collections::HashSet;
+
+use crate::base::{Board, CellSet, is_wall};
+use crate::play::{Direction, DIRECTIONS, step, tilt};
+
+/// Every piece position that can be solved, grouped by the length of its shortest solution.
+///
+/// The board's pieces are ignored; as many pieces as goals are placed. Entry 0 holds the solved
+/// position, entry d the positions solved in exactly d moves. Empty if the board has no goals.
+pub fn distance_layers(board: &Board) -> Vec<Vec<CellSet>> {
+    let goals = board.goal_set();
+    if goals.is_empty() {
+        return Vec::new();
+    }
+
+    let mut visited: HashSet<CellSet> = HashSet::new();
+    visited.insert(goals);
+    let mut layers = vec![vec![goals]];
+
+    loop {
+        let mut next_layer = Vec::new();
+        for pieces in layers.last().unwrap() {
+            for dir in &DIRECTIONS {
+                for prev in un_slide(board, *pieces, *dir) {
+                    if visited.insert(prev) {
+                        next_layer.push(prev);
+                    }
+                }
+            }
+        }
+
+        if next_layer.is_empty() {
+            return layers;
+        }
+        layers.push(next_layer);
+    }
+}
+
+/// Boards with the pieces placed as far as possible from a solution. See distance_layers().
+///
+/// Returns the solved board if no other position can reach it.
+pub fn hardest(board: &Board) -> Vec<Board> {
+    let layers = distance_layers(board);
+    let last = match layers.last() {
+        Some(last) => last,
+        None => return Vec::new(),
+    };
+
+    return last.iter().map(|pieces| {
+        let mut hard = board.clone();
+        hard.set_pieces(*pieces);
+        hard
+    }).collect();
+}
+
+/// Every unsolved piece position that becomes the given one when moving in a direction.
+pub fn un_slide(board: &Board, pieces: CellSet, direction: Direction) -> Vec<CellSet> {
+    let goals = board.goal_set();
+    let reverse = match direction {
+        Direction::Up => Direction::Down,
+        Direction::Right => Direction::Left,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+    };
+
+    // Each piece either stayed put, or came from further back along its row or column.
+    // A sliding piece stops on the first goal it meets, so it can't have crossed one.
+    let mut origins: Vec<Vec<usize>> = Vec::new();
+    for idx in pieces.iter() {
+        let mut candidates = vec![idx];
+        let mut pos = board.position(idx);
+        while let Some(prev) = step(board, pos, reverse) {
+            if is_wall(board[(prev.0 as usize, prev.1 as usize)]) {
+                break;
+            }
+
+            let prev_idx = board.cell_index(prev.0 as usize, prev.1 as usize);
+            candidates.push(prev_idx);
+            if goals.contains(prev_idx) {
+                break;
+            }
+            pos = prev;
+        }
+        origins.push(candidates);
+    }
+
+    // Try every combination of origins, keeping those that really slide into place.
+    let mut out = Vec::new();
+    let mut choice = vec![0; origins.len()];
+    loop {
+        let mut prev = CellSet::default();
+        for (candidates, pick) in origins.iter().zip(&choice) {
+            prev.insert(candidates[*pick]);
+        }
+
+        if prev.len() == pieces.len() && prev != pieces && !prev.is_superset(&goals) {
+            let (moved, _) = tilt(board, prev, direction);
+            if moved == pieces {
+                out.push(prev);
+            }
+        }
+
+        // Advance to the next combination, odometer style.
+        let mut i = 0;
+        while i < choice.len() {
+            choice[i] += 1;
+            if choice[i] < origins[i].len() {
+                break;
+            }
+            choice[i] = 0;
+            i += 1;
+        }
+        if i == choice.len() {
+            return out;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iters::holistic;
+    use crate::play::explore_space;
+
+    #[test]
+    fn layers_match_solution_length() {
+        let boards = holistic::solvable_random_multi(5, 5, 1, 3).take(150)
+            .chain(holistic::solvable_random_multi(5, 5, 2, 4).take(150));
+        for board in boards {
+            let layers = distance_layers(&board);
+            let layer = layers.iter().position(|layer| layer.contains(&board.piece_set()));
+            assert_eq!(layer, Some(explore_space(&board).num_moves), "{}", board);
+        }
+    }
+
+    #[test]
+    fn hardest_boards_are_the_last_layer() {
+        for board in holistic::hardest_serial(4, 4, 1).take(100).chain(holistic::hardest_serial(4, 3, 2).take(100)) {
+            let num_layers = distance_layers(&board).len();
+            let hardest = hardest(&board);
+            assert!(hardest.contains(&board), "{}", board);
+            for hard in hardest {
+                let stats = explore_space(&hard);
+                assert!(stats.solvable, "{}", hard);
+                assert_eq!(stats.num_moves, num_layers - 1, "{}", hard);
+            }
+        }
+    }
+
+    #[test]
+    fn un_slide_undoes_a_move() {
+        // The piece on the right came from the left end of the row, or from the middle.
+        let board: Board = "..#\nX@.\n".parse().unwrap();
+        let mut prev = un_slide(&board, board.piece_set(), Direction::Right);
+        prev.sort();
+        let origins: Vec<CellSet> = ["#..\nX@.\n", ".#.\nX@.\n"].iter()
+            .map(|text| text.parse::<Board>().unwrap().piece_set())
+            .collect();
+        assert_eq!(prev, origins);
+        assert!(un_slide(&board, board.piece_set(), Direction::Left).is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+//! Rule sets, so the iterators can generate and check boards under either one without a copy per
+//! rule set. See iters::solvable and iters::filled.
+
+use crate::base::Board;
+use crate::play::{self, ExploreStats};
+use crate::push;
+
+/// How pieces move: decides how many players a board needs and how it is solved.
+pub trait RuleSet {
+    /// Players placed on a generated board.
+    const NUM_PLAYERS: usize;
+
+    /// Shortest solution of a board under these rules.
+    fn explore_space(board: &Board) -> ExploreStats;
+}
+
+/// Sliding rules (see play). No player.
+pub struct Slide;
+
+/// Classic push rules (see push), with one player.
+pub struct Push;
+
+impl RuleSet for Slide {
+    const NUM_PLAYERS: usize = 0;
+
+    fn explore_space(board: &Board) -> ExploreStats {
+        play::explore_space(board)
+    }
+}
+
+impl RuleSet for Push {
+    const NUM_PLAYERS: usize = 1;
+
+    fn explore_space(board: &Board) -> ExploreStats {
+        push::explore_space(board)
+    }
+}
@@ -0,0 +1,330 @@
+//! Plain text level format. Reads and writes boards as one text row per board row.
+//!
+//! Two notations are supported (see `Notation`):
+//!
+//! | Cell                   | Native | XSB             |
+//! |------------------------|--------|-----------------|
+//! | `Cell::Floor`          | `.`    | `-`, `_` or ` ` |
+//! | `Cell::Wall`           | `X`    | `#`             |
+//! | `Cell::Piece`          | `#`    | `$`             |
+//! | `Cell::Goal`           | `@`    | `.`             |
+//! | `Cell::PieceOnGoal`    | `*`    | `*`             |
+//! | `Cell::Player`         | `P`    | `@`             |
+//! | `Cell::PlayerOnGoal`   | `+`    | `+`             |
+//!
+//! The native notation matches `base::print_board`; whitespace between cells is ignored, so its
+//! output can be pasted back in as is. XSB is the standard Sokoban notation; short rows are padded
+//! with walls, since everything outside the board behaves as a wall.
+//!
+//! `Board` implements `FromStr` and `Display` using the native notation.
+//!
+//! Move sequences are written in LURD notation: one letter per move, `U`, `R`, `D` or `L`.
+
+use std::{error, fmt};
+use std::str::FromStr;
+
+use crate::base::{self, Board, Cell, MAX_CELLS};
+use crate::play::Direction;
+
+/// Character set used to represent cells in text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Notation {
+    /// This crate's notation, as printed by `base::print_board`.
+    Native,
+    /// Standard Sokoban notation.
+    Xsb,
+}
+
+/// Reason a level could not be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// The text contains no rows.
+    Empty,
+    /// The character has no meaning in the notation being parsed.
+    UnexpectedChar(char),
+    /// A row has a different number of cells than the first one.
+    RowLength { expected: usize, found: usize },
+    /// The board has more cells than a `Board` can hold (see `base::MAX_CELLS`).
+    TooLarge { width: usize, height: usize },
+    /// The board doesn't have the (width, height) it was declared with.
+    Dimensions { expected: (usize, usize), found: (usize, usize) },
+    /// A second player. Boards hold at most one (see `Board::set_player`).
+    MultiplePlayers,
+}
+
+/// Error returned by `parse_board()`. Lines and columns start at 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match self.kind {
+            ParseErrorKind::Empty => write!(f, "empty level"),
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ParseErrorKind::RowLength { expected, found } => {
+                write!(f, "row has {} cells, expected {}", found, expected)
+            },
+            ParseErrorKind::TooLarge { width, height } => {
+                write!(f, "board is {}x{}, at most {} cells allowed", width, height, MAX_CELLS)
+            },
+            ParseErrorKind::Dimensions { expected, found } => {
+                write!(f, "board is {}x{}, expected {}x{}", found.0, found.1, expected.0, expected.1)
+            },
+            ParseErrorKind::MultiplePlayers => write!(f, "more than one player"),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parse a board in native notation. See `parse_board_with()`.
+pub fn parse_board(text: &str) -> Result<Board, ParseError> {
+    parse_board_with(text, Notation::Native)
+}
+
+/// Parse a board in the given notation. Blank lines before and after the board are ignored.
+pub fn parse_board_with(text: &str, notation: Notation) -> Result<Board, ParseError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let first_line = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(0);
+    let last_line = lines.iter().rposition(|line| !line.trim().is_empty()).unwrap_or(0);
+
+    let mut rows: Vec<Vec<Cell>> = Vec::new();
+    let mut width = 0;
+    let mut has_player = false;
+
+    for (idx, line) in lines.iter().enumerate().take(last_line + 1).skip(first_line) {
+        let mut row = Vec::new();
+        for (col, symbol) in line.chars().enumerate() {
+            let error = |kind| ParseError { line: idx + 1, column: col + 1, kind: kind };
+            if let Some(cell) = parse_cell(symbol, notation).map_err(error)? {
+                if base::has_player(cell) {
+                    if has_player {
+                        return Err(error(ParseErrorKind::MultiplePlayers));
+                    }
+                    has_player = true;
+                }
+                row.push(cell);
+            }
+        }
+
+        // XSB rows are commonly ragged; they get padded with walls by the Board.
+        if notation == Notation::Native {
+            if rows.is_empty() {
+                width = row.len();
+            } else if row.len() != width {
+                return Err(ParseError {
+                    line: idx + 1,
+                    column: 1,
+                    kind: ParseErrorKind::RowLength { expected: width, found: row.len() },
+                });
+            }
+        }
+        rows.push(row);
+    }
+
+    if rows.iter().all(|row| row.is_empty()) {
+        return Err(ParseError { line: 1, column: 1, kind: ParseErrorKind::Empty });
+    }
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let height = rows.len();
+    if width > u8::MAX as usize || height > u8::MAX as usize || width * height > MAX_CELLS {
+        return Err(ParseError {
+            line: first_line + 1,
+            column: 1,
+            kind: ParseErrorKind::TooLarge { width: width, height: height },
+        });
+    }
+
+    let mut board = Board::new(width as u8, height as u8);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            board.set(x, y, *cell);
+        }
+    }
+
+    return Ok(board);
+}
+
+/// Map a single character to a cell. Ok(None) means the character is ignored.
+fn parse_cell(symbol: char, notation: Notation) -> Result<Option<Cell>, ParseErrorKind> {
+    let cell = match notation {
+        Notation::Native => match symbol {
+            '.' => Cell::Floor,
+            'X' => Cell::Wall,
+            '#' => Cell::Piece,
+            '@' => Cell::Goal,
+            '*' => Cell::PieceOnGoal,
+            'P' => Cell::Player,
+            '+' => Cell::PlayerOnGoal,
+            ' ' | '\t' | '\r' => return Ok(None),
+            _ => return Err(ParseErrorKind::UnexpectedChar(symbol)),
+        },
+        Notation::Xsb => match symbol {
+            ' ' | '-' | '_' => Cell::Floor,
+            '#' => Cell::Wall,
+            '$' => Cell::Piece,
+            '.' => Cell::Goal,
+            '*' => Cell::PieceOnGoal,
+            '@' => Cell::Player,
+            '+' => Cell::PlayerOnGoal,
+            '\r' => return Ok(None),
+            _ => return Err(ParseErrorKind::UnexpectedChar(symbol)),
+        },
+    };
+
+    return Ok(Some(cell));
+}
+
+/// Character representing a cell in the given notation.
+pub fn cell_char(cell: Cell, notation: Notation) -> char {
+    match notation {
+        Notation::Native => match cell {
+            Cell::Floor => '.',
+            Cell::Wall => 'X',
+            Cell::Piece => '#',
+            Cell::Goal => '@',
+            Cell::PieceOnGoal => '*',
+            Cell::Player => 'P',
+            Cell::PlayerOnGoal => '+',
+        },
+        Notation::Xsb => match cell {
+            Cell::Floor => '-',
+            Cell::Wall => '#',
+            Cell::Piece => '$',
+            Cell::Goal => '.',
+            Cell::PieceOnGoal => '*',
+            Cell::Player => '@',
+            Cell::PlayerOnGoal => '+',
+        },
+    }
+}
+
+/// Write a board in native notation. See `format_board_with()`.
+pub fn format_board(board: &Board) -> String {
+    format_board_with(board, Notation::Native)
+}
+
+/// Write a board in the given notation, one line per row. Always ends with a newline.
+pub fn format_board_with(board: &Board, notation: Notation) -> String {
+    let mut out = String::new();
+    for row in board.rows() {
+        for cell in row {
+            out.push(cell_char(cell, notation));
+        }
+        out.push('\n');
+    }
+
+    return out;
+}
+
+/// Write a move sequence in LURD notation, e.g. `URDL`.
+pub fn format_moves(moves: &[Direction]) -> String {
+    moves.iter().map(|dir| match dir {
+        Direction::Up => 'U',
+        Direction::Right => 'R',
+        Direction::Down => 'D',
+        Direction::Left => 'L',
+    }).collect()
+}
+
+/// Parse a move sequence in LURD notation. Lowercase letters (plain moves, in Sokoban clients) are
+/// accepted too; whitespace is ignored.
+pub fn parse_moves(text: &str) -> Result<Vec<Direction>, ParseError> {
+    let mut moves = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        for (col, symbol) in line.chars().enumerate() {
+            let dir = match symbol.to_ascii_uppercase() {
+                'U' => Direction::Up,
+                'R' => Direction::Right,
+                'D' => Direction::Down,
+                'L' => Direction::Left,
+                c if c.is_whitespace() => continue,
+                _ => return Err(ParseError {
+                    line: idx + 1,
+                    column: col + 1,
+                    kind: ParseErrorKind::UnexpectedChar(symbol),
+                }),
+            };
+            moves.push(dir);
+        }
+    }
+
+    return Ok(moves);
+}
+
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Board, ParseError> {
+        parse_board(text)
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_board(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_round_trip() {
+        let text = "X.#.\n@*.P\n";
+        let board = parse_board(text).unwrap();
+        assert_eq!(board.dimensions(), (4, 2));
+        assert_eq!(board.get(2, 0), Cell::Piece);
+        assert_eq!(board.get(3, 1), Cell::Player);
+        assert_eq!(format_board(&board), text);
+    }
+
+    #[test]
+    fn xsb_round_trip() {
+        let text = "#-$.\n-*@-\n";
+        let board = parse_board_with(text, Notation::Xsb).unwrap();
+        assert_eq!(board, parse_board("X.#@\n.*P.\n").unwrap());
+        assert_eq!(format_board_with(&board, Notation::Xsb), text);
+    }
+
+    #[test]
+    fn xsb_short_rows_are_walls() {
+        let board = parse_board_with("\n  $.\n @\n\n", Notation::Xsb).unwrap();
+        assert_eq!(format_board_with(&board, Notation::Xsb), "--$.\n-@##\n");
+    }
+
+    #[test]
+    fn bad_character_position() {
+        let error = parse_board("\n. .\n. ?\n").unwrap_err();
+        assert_eq!(error, ParseError { line: 3, column: 3, kind: ParseErrorKind::UnexpectedChar('?') });
+        assert_eq!(error.to_string(), "3:3: unexpected character '?'");
+
+        let error = parse_board_with("#$.\n#X.\n", Notation::Xsb).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+    }
+
+    #[test]
+    fn one_player_at_most() {
+        let error = parse_board("P..\n.+.\n..P\n").unwrap_err();
+        assert_eq!(error, ParseError { line: 2, column: 2, kind: ParseErrorKind::MultiplePlayers });
+        assert_eq!(error.to_string(), "2:2: more than one player");
+
+        let error = parse_board_with("#@$.@\n", Notation::Xsb).unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (1, 5, ParseErrorKind::MultiplePlayers));
+    }
+
+    #[test]
+    fn moves_round_trip() {
+        let moves = parse_moves("ur\nDL").unwrap();
+        assert_eq!(moves, vec![Direction::Up, Direction::Right, Direction::Down, Direction::Left]);
+        assert_eq!(format_moves(&moves), "URDL");
+        assert_eq!(parse_moves("UU\nRx").unwrap_err().column, 2);
+    }
+}
@@ -2,25 +2,29 @@
 
 use crate::base::{Board, Cell};
 
-/// Given an initial Floor position in a board and a Zone tag, mark the connected component.
-fn paint_component(board: &mut Board, x: usize, y: usize, component: u32) -> usize {
-    let size = board.len() as i32;
-    let cell_type = Cell::Zone(component);
+/// Connected component tag for every cell of a board, indexed as zones[y][x]. None for walls.
+pub type Zones = Vec<Vec<Option<u32>>>;
+
+/// Given an initial non-wall position in a board and a zone tag, mark the connected component.
+fn paint_component(board: &Board, zones: &mut Zones, x: usize, y: usize, component: u32) -> usize {
+    let (width, height) = board.dimensions();
+    let (width, height) = (width as i32, height as i32);
     let mut pending = vec![(x, y)];
     let mut cell_count = 0;
+    zones[y][x] = Some(component);
 
     while let Some((i, j)) = pending.pop() {
-        board[j][i] = cell_type;
         cell_count += 1;
         let ii = i as i32;
         let jj = j as i32;
         let neighbors: Vec<(i32, i32)> =
             vec![(ii - 1, jj), (ii + 1, jj), (ii, jj - 1), (ii, jj + 1)];
         for (kk, ll) in neighbors {
-            if kk >= 0 && ll >= 0 && kk < size && ll < size {
+            if kk >= 0 && ll >= 0 && kk < width && ll < height {
                 let k = kk as usize;
                 let l = ll as usize;
-                if let Cell::Floor = board[l][k] {
+                if board[(k, l)] != Cell::Wall && zones[l][k].is_none() {
+                    zones[l][k] = Some(component);
                     pending.push((k, l));
                 }
             }
@@ -30,39 +34,38 @@ fn paint_component(board: &mut Board, x: usize, y: usize, component: u32) -> usi
     return cell_count;
 }
 
-/// Change the Floor tiles in a board for Zone tiles, indicating connected components.
-/// 
+/// Tag the non-wall cells in a board with their connected component.
+///
 /// # Arguments
-/// 
-/// `board` - Board to *modify*.
-/// 
+///
+/// `board` - Board to analyze.
+///
 /// # Return value
-/// 
-/// Vector of cell counts. 
-/// Entry `i` corresponds to the number of tiles in connected component `i`. 
-/// Corresponds to tiles of type `Cell::Zone(i)`.
-pub fn mark_components(board: &mut Board) -> Vec<usize> {
-    let size = board.len();
+///
+/// Zone map and vector of cell counts.
+/// Entry `i` of the counts corresponds to the number of tiles in connected component `i`,
+/// i.e. the tiles tagged `Some(i)` in the zone map.
+pub fn mark_components(board: &Board) -> (Zones, Vec<usize>) {
+    let (width, height) = board.dimensions();
+    let mut zones: Zones = vec![vec![None; width]; height];
     let mut component_count = 0;
     let mut cell_counts = Vec::new();
-    for y in 0..size {
-        for x in 0..size {
-            if let Cell::Floor = board[y][x] {
-                let cell_count = paint_component(board, x, y, component_count);
+    for y in 0..height {
+        for x in 0..width {
+            if board[(x, y)] != Cell::Wall && zones[y][x].is_none() {
+                let cell_count = paint_component(board, &mut zones, x, y, component_count);
                 component_count += 1;
                 cell_counts.push(cell_count);
             }
         }
     }
 
-    return cell_counts;
+    return (zones, cell_counts);
 }
 
 /// Remove all connected components except biggest.
 pub fn remove_components(board: &mut Board) {
-    let size = board.len();
-
-    let cell_counts = mark_components(board);
+    let (zones, cell_counts) = mark_components(board);
     if cell_counts.is_empty() {
         return;
     }
@@ -76,13 +79,11 @@ pub fn remove_components(board: &mut Board) {
         }
     }
 
-    for y in 0..size {
-        for x in 0..size {
-            if let Cell::Zone(idx) = board[y][x] {
-                if idx == max_idx as u32 {
-                    board[y][x] = Cell::Floor;
-                } else {
-                    board[y][x] = Cell::Wall;
+    for (y, row) in zones.iter().enumerate() {
+        for (x, zone) in row.iter().enumerate() {
+            if let Some(idx) = zone {
+                if *idx != max_idx as u32 {
+                    board.set(x, y, Cell::Wall);
                 }
             }
         }
@@ -19,7 +19,6 @@ pub struct CellStats {
 
 /// Calculate aggregate statistics for the presence of a Cell type in the Board (see CellStats).
 pub fn cell_stats(board: &Board, cell: Cell) -> CellStats {
-    let size = board.len();
     let mut stats = CellStats {
         count: 0,
         min: None,
@@ -27,21 +26,19 @@ pub fn cell_stats(board: &Board, cell: Cell) -> CellStats {
         rank: (0, 0),
     };
 
-    for y in 0..size {
-        for x in 0..size {
-            if board[y][x] == cell {
-                stats.count += 1;
-
-                stats.min = match stats.min {
-                    Some((z, w)) => Some((cmp::min(x, z), cmp::min(y, w))),
-                    None => Some((x, y)),
-                };
-
-                stats.max = match stats.max {
-                    Some((z, w)) => Some((cmp::max(x, z), cmp::max(y, w))),
-                    None => Some((x, y)),
-                };
-            }
+    for ((x, y), other) in board.iter() {
+        if other == cell {
+            stats.count += 1;
+
+            stats.min = match stats.min {
+                Some((z, w)) => Some((cmp::min(x, z), cmp::min(y, w))),
+                None => Some((x, y)),
+            };
+
+            stats.max = match stats.max {
+                Some((z, w)) => Some((cmp::max(x, z), cmp::max(y, w))),
+                None => Some((x, y)),
+            };
         }
     }
 
@@ -1,39 +1,56 @@
 //! Utilities for calculating all square symmetries on a board.
+//!
+//! Square boards have the full D4 group of symmetries. Rectangular boards only keep the shape
+//! under the D2 subgroup (identity, half turn and both reflections); see `all()`.
 
 use crate::base::Board;
 
-/// Counter-clockwise 90° rotation.
+/// Counter-clockwise 90° rotation. A width x height board becomes height x width.
 pub fn rotate(board: &Board) -> Board {
-    let size = board.len();
-    let mut output = Vec::new();
-    for y in 0..size {
-        let mut row = Vec::new();
-        for x in 0..size {
-            row.push(board[x][size - 1 - y]);
+    let (width, height) = board.dimensions();
+    let mut output = Board::new(height as u8, width as u8);
+    for y in 0..width {
+        for x in 0..height {
+            output.set(x, y, board[(width - 1 - y, x)]);
         }
-        output.push(row);
     }
 
     return output;
 }
 
-/// Reflect over the vertical axis x = size / 2.
+/// 180° rotation.
+pub fn rotate_half(board: &Board) -> Board {
+    return reflect_y(&reflect_x(board));
+}
+
+/// Reflect over the vertical axis x = width / 2.
 pub fn reflect_x(board: &Board) -> Board {
-    let size = board.len();
-    let mut output = Vec::new();
-    for y in 0..size {
-        let mut row = Vec::new();
-        for x in 0..size {
-            row.push(board[y][size - 1 - x]);
+    let (width, height) = board.dimensions();
+    let mut output = Board::new(width as u8, height as u8);
+    for y in 0..height {
+        for x in 0..width {
+            output.set(x, y, board[(width - 1 - x, y)]);
         }
-        output.push(row);
     }
 
     return output;
 }
 
-/// Calculate all D4 (dihedral 4) symmetries of a board.
-pub fn all(board: &Board) -> Vec<Board> {
+/// Reflect over the horizontal axis y = height / 2.
+pub fn reflect_y(board: &Board) -> Board {
+    let (width, height) = board.dimensions();
+    let mut output = Board::new(width as u8, height as u8);
+    for y in 0..height {
+        for x in 0..width {
+            output.set(x, y, board[(x, height - 1 - y)]);
+        }
+    }
+
+    return output;
+}
+
+/// Calculate all D4 (dihedral 4) symmetries of a square board.
+pub fn d4(board: &Board) -> Vec<Board> {
     let e = board.clone();
     let a = rotate(board);
     let a2 = rotate(&a);
@@ -45,3 +62,30 @@ pub fn all(board: &Board) -> Vec<Board> {
 
     return vec![e, a, a2, a3, b, ab, a2b, a3b];
 }
+
+/// Calculate all D2 (dihedral 2) symmetries of a board. These preserve the shape of any rectangle.
+pub fn d2(board: &Board) -> Vec<Board> {
+    let e = board.clone();
+    let a2 = rotate_half(board);
+    let b = reflect_x(board);
+    let a2b = reflect_y(board);
+
+    return vec![e, a2, b, a2b];
+}
+
+/// Calculate all symmetries of a board that preserve its shape: D4 if square, D2 otherwise.
+pub fn all(board: &Board) -> Vec<Board> {
+    let (width, height) = board.dimensions();
+    if width == height {
+        return d4(board);
+    }
+
+    return d2(board);
+}
+
+/// Representative of the board's symmetry class: the smallest of all() by Board ordering.
+///
+/// Two boards are symmetries of each other if and only if they have the same canonical form.
+pub fn canonical(board: &Board) -> Board {
+    return all(board).into_iter().min().unwrap();
+}
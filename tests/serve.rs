@@ -0,0 +1,127 @@
+//! Drive `sokoban serve` through a pipe, as a bot would.
+#![cfg(feature = "serde")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+struct Server {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl Server {
+    fn start() -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_sokoban"))
+            .arg("serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("can't start sokoban serve");
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+        return Server { child: child, input: input, output: output };
+    }
+
+    /// Send one request and wait for its response.
+    fn send(&mut self, request: Value) -> Value {
+        writeln!(self.input, "{}", request).unwrap();
+        self.input.flush().unwrap();
+
+        let mut line = String::new();
+        self.output.read_line(&mut line).unwrap();
+        return serde_json::from_str(&line).unwrap();
+    }
+
+    /// Close standard input and check the server exits cleanly.
+    fn stop(mut self) {
+        drop(self.input);
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+#[test]
+fn play_a_level() {
+    let mut server = Server::start();
+
+    let response = server.send(json!({ "command": "state" }));
+    assert_eq!(response["type"], "error");
+
+    let board = json!({ "width": 3, "height": 2, "rows": ["#..", "..@"] });
+    let response = server.send(json!({ "command": "load", "board": board }));
+    assert_eq!(response["type"], "state");
+    assert_eq!(response["pieces"], json!([[0, 0]]));
+    assert_eq!(response["moves"], 0);
+    assert_eq!(response["solvable"], true);
+    assert_eq!(response["remaining_moves"], 2);
+
+    let response = server.send(json!({ "command": "hint" }));
+    assert_eq!(response, json!({ "type": "hint", "direction": "Down", "solution": ["Down", "Right"] }));
+
+    let response = server.send(json!({ "command": "move", "direction": "Right" }));
+    assert_eq!(response["pieces"], json!([[2, 0]]));
+    assert_eq!(response["moves"], 1);
+    assert_eq!(response["victory"], false);
+    assert_eq!(response["remaining_moves"], 1);
+
+    let response = server.send(json!({ "command": "move", "direction": "Down" }));
+    assert_eq!(response["pieces"], json!([[2, 1]]));
+    assert_eq!(response["victory"], true);
+    assert_eq!(response["remaining_moves"], 0);
+
+    let response = server.send(json!({ "command": "move", "direction": "Up" }));
+    assert_eq!(response["type"], "error");
+
+    let response = server.send(json!({ "command": "undo" }));
+    assert_eq!(response["pieces"], json!([[2, 0]]));
+    assert_eq!(response["moves"], 1);
+    assert_eq!(response["victory"], false);
+
+    let response = server.send(json!({ "command": "undo" }));
+    assert_eq!(response["board"], board);
+
+    let response = server.send(json!({ "command": "undo" }));
+    assert_eq!(response["type"], "error");
+
+    server.stop();
+}
+
+#[test]
+fn push_rules_and_bad_requests() {
+    let mut server = Server::start();
+
+    let response = server.send(json!({ "command": "jump" }));
+    assert_eq!(response["type"], "error");
+
+    // A player makes it a push level. Walking off the board is not a move.
+    let board = json!({ "width": 4, "height": 1, "rows": ["P#.@"] });
+    let response = server.send(json!({ "command": "load", "board": board }));
+    assert_eq!(response["player"], json!([0, 0]));
+    assert_eq!(response["remaining_moves"], 2);
+
+    let response = server.send(json!({ "command": "move", "direction": "Up" }));
+    assert_eq!(response["moves"], 0);
+
+    let response = server.send(json!({ "command": "move", "direction": "Right" }));
+    assert_eq!(response["player"], json!([1, 0]));
+    assert_eq!(response["pieces"], json!([[2, 0]]));
+
+    let response = server.send(json!({ "command": "move", "direction": "Right" }));
+    assert_eq!(response["victory"], true);
+
+    // The box can only be pushed into the corner, away from the goal.
+    let board = json!({ "width": 3, "height": 2, "rows": ["P#.", "..@"] });
+    let response = server.send(json!({ "command": "load", "board": board }));
+    assert_eq!(response["solvable"], false);
+    assert_eq!(response["remaining_moves"], Value::Null);
+
+    let response = server.send(json!({ "command": "hint" }));
+    assert_eq!(response["direction"], Value::Null);
+
+    let response = server.send(json!({ "command": "load", "board": { "width": 2, "height": 1, "rows": ["..."] } }));
+    assert_eq!(response["type"], "error");
+
+    server.stop();
+}